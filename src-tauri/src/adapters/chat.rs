@@ -0,0 +1,222 @@
+//! OpenAI-compatible `/chat/completions` building and parsing, shared by every
+//! adapter that speaks it
+
+use super::{ProviderAdapter, StreamDelta, ToolCallDelta};
+use crate::types::{
+    FunctionCall, LLMRequest, LLMResponse, Message, PerformanceMetrics, TokenLogprob, ToolCall,
+    TopLogprob, UsageMetrics,
+};
+use serde_json::Value;
+
+/// Build a `/chat/completions` body, letting the adapter add reasoning parameters
+pub fn build_body<A: ProviderAdapter + ?Sized>(
+    adapter: &A,
+    request: &LLMRequest,
+    stream: bool,
+) -> Value {
+    let mut body = serde_json::json!({
+        "model": request.model.clone(),
+        "messages": messages(&request.messages),
+        "temperature": request.temperature,
+        "max_tokens": request.max_tokens,
+        "stream": stream
+    });
+
+    if !request.tools.is_empty() {
+        body["tools"] = serde_json::json!(request.tools);
+    }
+    if let Some(tool_choice) = &request.tool_choice {
+        body["tool_choice"] = tool_choice.clone();
+    }
+    if let Some(response_format) = &request.response_format {
+        body["response_format"] = serde_json::json!(response_format);
+    }
+    if request.logprobs {
+        body["logprobs"] = serde_json::json!(true);
+        if let Some(top_logprobs) = request.top_logprobs {
+            body["top_logprobs"] = serde_json::json!(top_logprobs);
+        }
+    }
+
+    if stream {
+        // Ask for a final usage chunk so streaming runs report token counts
+        body["stream_options"] = serde_json::json!({ "include_usage": true });
+    }
+
+    if let Some(reasoning_config) = &request.reasoning_config {
+        adapter.apply_reasoning(&mut body, request, reasoning_config);
+    }
+
+    body
+}
+
+/// Serialize chat messages; assistant turns that only call tools send `content: null`
+pub fn messages(messages: &[Message]) -> Vec<Value> {
+    messages
+        .iter()
+        .map(|message| {
+            let mut value = serde_json::json!(message);
+            if !message.tool_calls.is_empty() && message.content.is_empty() {
+                value["content"] = Value::Null;
+            }
+            value
+        })
+        .collect()
+}
+
+/// Parse a `/chat/completions` response, with reasoning output extracted by the adapter
+pub fn parse_response<A: ProviderAdapter + ?Sized>(adapter: &A, parsed: &Value) -> LLMResponse {
+    let message = &parsed["choices"][0]["message"];
+    let (reasoning_content, thinking_blocks) = adapter.parse_reasoning(message);
+
+    let tool_calls = message["tool_calls"]
+        .as_array()
+        .map(|calls| calls.iter().map(parse_tool_call).collect())
+        .unwrap_or_default();
+
+    LLMResponse {
+        content: message["content"].as_str().unwrap_or("").to_string(),
+        usage: adapter.parse_usage(&parsed["usage"]),
+        finish_reason: parsed["choices"][0]["finish_reason"]
+            .as_str()
+            .unwrap_or("stop")
+            .to_string(),
+        reasoning_content,
+        thinking_blocks,
+        reasoning_provider: adapter.reasoning_provider(),
+        cancelled: false,
+        metrics: PerformanceMetrics::default(),
+        tool_calls,
+        validation: None,
+        provider_resolution: None,
+        logprobs: parse_logprobs(&parsed["choices"][0]["logprobs"]["content"]),
+        logprob_stats: None,
+        warnings: Vec::new(),
+    }
+}
+
+/// Parse a list of `{token, logprob, top_logprobs}` entries, as in chat
+/// `logprobs.content` and Responses API `output_text` parts
+pub fn parse_logprobs(entries: &Value) -> Vec<TokenLogprob> {
+    let Some(entries) = entries.as_array() else {
+        return Vec::new();
+    };
+
+    entries
+        .iter()
+        .filter_map(|entry| {
+            Some(TokenLogprob {
+                token: entry["token"].as_str()?.to_string(),
+                logprob: entry["logprob"].as_f64()?,
+                top_logprobs: entry["top_logprobs"]
+                    .as_array()
+                    .map(|top| {
+                        top.iter()
+                            .filter_map(|alt| {
+                                Some(TopLogprob {
+                                    token: alt["token"].as_str()?.to_string(),
+                                    logprob: alt["logprob"].as_f64()?,
+                                })
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Parse one entry of `message.tool_calls`. Some servers return `arguments` as an
+/// object rather than a JSON string, so it is re-serialized in that case.
+fn parse_tool_call(call: &Value) -> ToolCall {
+    let arguments = &call["function"]["arguments"];
+
+    ToolCall {
+        id: call["id"].as_str().unwrap_or_default().to_string(),
+        kind: call["type"].as_str().unwrap_or("function").to_string(),
+        function: FunctionCall {
+            name: call["function"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            arguments: match arguments {
+                Value::String(s) => s.clone(),
+                Value::Null => String::new(),
+                other => other.to_string(),
+            },
+        },
+    }
+}
+
+/// Extract `delta.tool_calls` fragments from a chat completions chunk
+fn parse_tool_call_deltas(delta: &Value) -> Vec<ToolCallDelta> {
+    delta["tool_calls"]
+        .as_array()
+        .map(|calls| {
+            calls
+                .iter()
+                .map(|call| ToolCallDelta {
+                    index: call["index"].as_u64().unwrap_or(0) as usize,
+                    id: call["id"].as_str().map(|s| s.to_string()),
+                    name: call["function"]["name"].as_str().map(|s| s.to_string()),
+                    arguments: call["function"]["arguments"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Extract the incremental output from one chat completions chunk
+pub fn parse_stream_event<A: ProviderAdapter + ?Sized>(adapter: &A, parsed: &Value) -> StreamDelta {
+    let delta = &parsed["choices"][0]["delta"];
+
+    StreamDelta {
+        content: delta["content"].as_str().map(|s| s.to_string()),
+        // vLLM and some gateways use `reasoning` instead of `reasoning_content`
+        reasoning: delta["reasoning_content"]
+            .as_str()
+            .or_else(|| delta["reasoning"].as_str())
+            .map(|s| s.to_string()),
+        // Only the final chunk carries usage (with empty `choices`)
+        usage: adapter.parse_usage(&parsed["usage"]),
+        tool_calls: parse_tool_call_deltas(delta),
+        logprobs: parse_logprobs(&parsed["choices"][0]["logprobs"]["content"]),
+        finish_reason: parsed["choices"][0]["finish_reason"]
+            .as_str()
+            .map(|s| s.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Map OpenAI-compatible usage to `UsageMetrics`, including the nested
+/// `completion_tokens_details`/`prompt_tokens_details` breakdowns
+pub fn parse_usage(usage: &Value) -> Option<UsageMetrics> {
+    let prompt_tokens = usage["prompt_tokens"].as_u64()? as u32;
+    let completion_tokens = usage["completion_tokens"].as_u64()? as u32;
+
+    let reasoning_tokens = usage["completion_tokens_details"]["reasoning_tokens"]
+        .as_u64()
+        .or_else(|| usage["reasoning_tokens"].as_u64())
+        .map(|t| t as u32);
+
+    // DeepSeek reports cache hits as `prompt_cache_hit_tokens`
+    let cache_read_tokens = usage["prompt_tokens_details"]["cached_tokens"]
+        .as_u64()
+        .or_else(|| usage["prompt_cache_hit_tokens"].as_u64())
+        .map(|t| t as u32);
+
+    Some(UsageMetrics {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: usage["total_tokens"]
+            .as_u64()
+            .map(|t| t as u32)
+            .unwrap_or(prompt_tokens + completion_tokens),
+        reasoning_tokens,
+        cache_creation_tokens: None,
+        cache_read_tokens,
+    })
+}
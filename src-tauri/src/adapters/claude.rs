@@ -0,0 +1,427 @@
+use super::{ProviderAdapter, StreamDelta, ToolCallDelta};
+use crate::media::parse_data_url;
+use crate::types::{
+    ApiStyle, ContentPart, Endpoint, FunctionCall, LLMRequest, LLMResponse, Message,
+    MessageContent, PerformanceMetrics, ReasoningConfig, ReasoningProvider, ThinkingBlock,
+    ToolCall, UsageMetrics,
+};
+use reqwest::RequestBuilder;
+use serde_json::Value;
+
+/// Anthropic API version sent with every Messages API request
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Default thinking budget when Claude thinking is enabled without an explicit budget
+const DEFAULT_THINKING_BUDGET_TOKENS: u32 = 1024;
+
+/// Claude over the native Anthropic Messages API (`{url}/messages`)
+pub struct Claude;
+
+impl ProviderAdapter for Claude {
+    fn name(&self) -> &'static str {
+        "claude"
+    }
+
+    fn api_style(&self) -> ApiStyle {
+        ApiStyle::AnthropicMessages
+    }
+
+    fn reasoning_provider(&self) -> Option<ReasoningProvider> {
+        Some(ReasoningProvider::Claude)
+    }
+
+    fn generation_url(&self, endpoint: &Endpoint, _request: &LLMRequest, _stream: bool) -> String {
+        format!("{}/messages", endpoint.url)
+    }
+
+    fn apply_headers(&self, req_builder: RequestBuilder, endpoint: &Endpoint) -> RequestBuilder {
+        let mut req_builder = match &endpoint.api_key {
+            Some(api_key) => req_builder.header("x-api-key", api_key),
+            None => req_builder,
+        };
+
+        // Custom headers may pin a different version, so only send the default if absent
+        if !endpoint
+            .headers
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case("anthropic-version"))
+        {
+            req_builder = req_builder.header("anthropic-version", ANTHROPIC_VERSION);
+        }
+
+        req_builder
+    }
+
+    fn build_body(&self, request: &LLMRequest, stream: bool) -> Result<Value, String> {
+        build_body(request, stream)
+    }
+
+    fn parse_response(&self, parsed: &Value) -> Result<LLMResponse, String> {
+        Ok(parse_response(parsed))
+    }
+
+    fn parse_stream_event(&self, parsed: &Value) -> Result<StreamDelta, String> {
+        match parsed["type"].as_str() {
+            Some("error") => Err(format!(
+                "Stream error: {}",
+                parsed["error"]["message"]
+                    .as_str()
+                    .unwrap_or("Unknown error")
+            )),
+            Some("content_block_delta") => {
+                let delta = &parsed["delta"];
+                let index = parsed["index"].as_u64().unwrap_or(0);
+
+                Ok(match delta["type"].as_str() {
+                    Some("text_delta") => StreamDelta {
+                        content: delta["text"].as_str().map(|s| s.to_string()),
+                        ..Default::default()
+                    },
+                    Some("thinking_delta") => StreamDelta {
+                        thinking: delta["thinking"].as_str().map(|s| (index, s.to_string())),
+                        ..Default::default()
+                    },
+                    Some("input_json_delta") => StreamDelta {
+                        tool_calls: vec![ToolCallDelta {
+                            index: index as usize,
+                            id: None,
+                            name: None,
+                            arguments: delta["partial_json"]
+                                .as_str()
+                                .unwrap_or_default()
+                                .to_string(),
+                        }],
+                        ..Default::default()
+                    },
+                    _ => StreamDelta::default(),
+                })
+            }
+            // A tool_use block opens with its ID and name; arguments follow as deltas
+            Some("content_block_start") if parsed["content_block"]["type"] == "tool_use" => {
+                let block = &parsed["content_block"];
+                Ok(StreamDelta {
+                    tool_calls: vec![ToolCallDelta {
+                        index: parsed["index"].as_u64().unwrap_or(0) as usize,
+                        id: block["id"].as_str().map(|s| s.to_string()),
+                        name: block["name"].as_str().map(|s| s.to_string()),
+                        arguments: String::new(),
+                    }],
+                    ..Default::default()
+                })
+            }
+            // Input and cache tokens arrive up front with the message
+            Some("message_start") => Ok(StreamDelta {
+                usage: parse_usage(&parsed["message"]["usage"]),
+                ..Default::default()
+            }),
+            // Carries the stop reason and only the cumulative `output_tokens`
+            Some("message_delta") => Ok(StreamDelta {
+                finish_reason: parsed["delta"]["stop_reason"]
+                    .as_str()
+                    .map(|s| s.to_string()),
+                usage: parsed["usage"]["output_tokens"]
+                    .as_u64()
+                    .map(|output| UsageMetrics {
+                        prompt_tokens: 0,
+                        completion_tokens: output as u32,
+                        total_tokens: output as u32,
+                        reasoning_tokens: None,
+                        cache_creation_tokens: None,
+                        cache_read_tokens: None,
+                    }),
+                ..Default::default()
+            }),
+            // content_block_start/stop, message_stop, ping, ...
+            _ => Ok(StreamDelta::default()),
+        }
+    }
+
+    fn parse_usage(&self, usage: &Value) -> Option<UsageMetrics> {
+        parse_usage(usage)
+    }
+}
+
+/// Claude behind an OpenAI-compatible proxy, which forwards the `thinking` parameter
+/// and returns thinking as content blocks
+pub struct ClaudeCompatible;
+
+impl ProviderAdapter for ClaudeCompatible {
+    fn name(&self) -> &'static str {
+        "claude-compatible"
+    }
+
+    fn reasoning_provider(&self) -> Option<ReasoningProvider> {
+        Some(ReasoningProvider::Claude)
+    }
+
+    fn apply_reasoning(&self, body: &mut Value, _request: &LLMRequest, config: &ReasoningConfig) {
+        if config.enable_thinking {
+            let mut thinking = serde_json::json!({
+                "type": "enabled"
+            });
+            if let Some(budget) = config.thinking_budget_tokens {
+                thinking["budget_tokens"] = serde_json::json!(budget);
+            }
+            body["thinking"] = thinking;
+        }
+    }
+
+    fn parse_reasoning(&self, message: &Value) -> (Option<String>, Vec<ThinkingBlock>) {
+        let thinking_blocks = message["content"]
+            .as_array()
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .filter(|block| block["type"] == "thinking")
+                    .map(|block| ThinkingBlock {
+                        content: block["thinking"].as_str().unwrap_or("").to_string(),
+                        summary: block["summary"].as_str().map(|s| s.to_string()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        (None, thinking_blocks)
+    }
+}
+
+/// Convert message content to Anthropic's: plain strings pass through, parts become
+/// `text` and `image` blocks. Anthropic has no audio input.
+fn content(content: &MessageContent) -> Result<Value, String> {
+    let parts = match content {
+        MessageContent::Text(text) => return Ok(serde_json::json!(text)),
+        MessageContent::Parts(parts) => parts,
+    };
+
+    let blocks = parts
+        .iter()
+        .map(|part| match part {
+            ContentPart::Text { text } => Ok(serde_json::json!({ "type": "text", "text": text })),
+            ContentPart::ImageUrl { image_url } => {
+                let source = match parse_data_url(&image_url.url) {
+                    Some((media_type, data)) => serde_json::json!({
+                        "type": "base64",
+                        "media_type": media_type,
+                        "data": data
+                    }),
+                    None => serde_json::json!({ "type": "url", "url": image_url.url }),
+                };
+                Ok(serde_json::json!({ "type": "image", "source": source }))
+            }
+            ContentPart::InputAudio { .. } => {
+                Err("The Anthropic Messages API does not accept audio input".to_string())
+            }
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(Value::Array(blocks))
+}
+
+/// Convert chat messages to Anthropic's format: tool calls become `tool_use` blocks and
+/// `tool` messages become `tool_result` blocks in a user turn
+fn messages(messages: &[Message]) -> Result<Vec<Value>, String> {
+    let mut converted: Vec<Value> = Vec::new();
+
+    for message in messages.iter().filter(|m| m.role != "system") {
+        if message.role == "tool" {
+            let result = serde_json::json!({
+                "type": "tool_result",
+                "tool_use_id": message.tool_call_id.clone().unwrap_or_default(),
+                "content": content(&message.content)?
+            });
+
+            // Results for parallel calls must share a single user turn
+            if let Some(blocks) = converted
+                .last_mut()
+                .filter(|last| last["role"] == "user")
+                .and_then(|last| last["content"].as_array_mut())
+            {
+                blocks.push(result);
+            } else {
+                converted.push(serde_json::json!({ "role": "user", "content": [result] }));
+            }
+            continue;
+        }
+
+        if message.tool_calls.is_empty() {
+            converted.push(serde_json::json!({
+                "role": message.role,
+                "content": content(&message.content)?
+            }));
+            continue;
+        }
+
+        let mut blocks = Vec::new();
+        if !message.content.is_empty() {
+            blocks.push(serde_json::json!({ "type": "text", "text": message.content.text() }));
+        }
+        for call in &message.tool_calls {
+            blocks.push(serde_json::json!({
+                "type": "tool_use",
+                "id": call.id,
+                "name": call.function.name,
+                "input": serde_json::from_str::<Value>(&call.function.arguments)
+                    .unwrap_or_else(|_| serde_json::json!({}))
+            }));
+        }
+        converted.push(serde_json::json!({ "role": message.role, "content": blocks }));
+    }
+
+    Ok(converted)
+}
+
+/// Map an OpenAI `tool_choice` to Anthropic's (`required` is `any` there)
+fn tool_choice(tool_choice: &Value) -> Value {
+    match tool_choice.as_str() {
+        Some("required") => serde_json::json!({ "type": "any" }),
+        Some(kind) => serde_json::json!({ "type": kind }),
+        None => match tool_choice["function"]["name"].as_str() {
+            Some(name) => serde_json::json!({ "type": "tool", "name": name }),
+            None => tool_choice.clone(),
+        },
+    }
+}
+
+/// Build Anthropic `/messages` body: system prompts move to the top-level `system` field
+fn build_body(request: &LLMRequest, stream: bool) -> Result<Value, String> {
+    let system: Vec<String> = request
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.text())
+        .collect();
+
+    let mut body = serde_json::json!({
+        "model": request.model.clone(),
+        "messages": messages(&request.messages)?,
+        "max_tokens": request.max_tokens,
+        "stream": stream
+    });
+
+    if !system.is_empty() {
+        body["system"] = serde_json::json!(system.join("\n\n"));
+    }
+
+    if !request.tools.is_empty() {
+        let tools: Vec<Value> = request
+            .tools
+            .iter()
+            .map(|tool| {
+                let mut converted = serde_json::json!({
+                    "name": tool.function.name,
+                    "input_schema": tool
+                        .function
+                        .parameters
+                        .clone()
+                        .unwrap_or_else(|| serde_json::json!({ "type": "object" }))
+                });
+                if let Some(description) = &tool.function.description {
+                    converted["description"] = serde_json::json!(description);
+                }
+                converted
+            })
+            .collect();
+        body["tools"] = serde_json::json!(tools);
+    }
+    if let Some(choice) = &request.tool_choice {
+        body["tool_choice"] = tool_choice(choice);
+    }
+
+    let thinking_enabled = request
+        .reasoning_config
+        .as_ref()
+        .is_some_and(|c| c.enable_thinking);
+
+    if thinking_enabled {
+        let budget = request
+            .reasoning_config
+            .as_ref()
+            .and_then(|c| c.thinking_budget_tokens)
+            .unwrap_or(DEFAULT_THINKING_BUDGET_TOKENS);
+        body["thinking"] = serde_json::json!({
+            "type": "enabled",
+            "budget_tokens": budget
+        });
+    } else {
+        // Anthropic rejects a custom temperature while extended thinking is enabled
+        body["temperature"] = serde_json::json!(request.temperature);
+    }
+
+    Ok(body)
+}
+
+/// Parse an Anthropic `/messages` response made of typed content blocks
+fn parse_response(parsed: &Value) -> LLMResponse {
+    let blocks = parsed["content"].as_array().cloned().unwrap_or_default();
+
+    let content = blocks
+        .iter()
+        .filter(|block| block["type"] == "text")
+        .filter_map(|block| block["text"].as_str())
+        .collect::<String>();
+
+    let thinking_blocks = blocks
+        .iter()
+        .filter(|block| block["type"] == "thinking")
+        .map(|block| ThinkingBlock {
+            content: block["thinking"].as_str().unwrap_or("").to_string(),
+            summary: None,
+        })
+        .collect();
+
+    let tool_calls = blocks
+        .iter()
+        .filter(|block| block["type"] == "tool_use")
+        .map(|block| ToolCall {
+            id: block["id"].as_str().unwrap_or_default().to_string(),
+            kind: "function".to_string(),
+            function: FunctionCall {
+                name: block["name"].as_str().unwrap_or_default().to_string(),
+                arguments: block["input"].to_string(),
+            },
+        })
+        .collect();
+
+    LLMResponse {
+        content,
+        usage: parse_usage(&parsed["usage"]),
+        finish_reason: parsed["stop_reason"]
+            .as_str()
+            .unwrap_or("end_turn")
+            .to_string(),
+        reasoning_content: None,
+        thinking_blocks,
+        reasoning_provider: Some(ReasoningProvider::Claude),
+        cancelled: false,
+        metrics: PerformanceMetrics::default(),
+        tool_calls,
+        validation: None,
+        provider_resolution: None,
+        logprobs: Vec::new(),
+        logprob_stats: None,
+        warnings: Vec::new(),
+    }
+}
+
+/// Map Anthropic usage to `UsageMetrics`. Anthropic's `input_tokens` excludes cached
+/// tokens, so prompt tokens add them back to stay comparable with OpenAI's count.
+fn parse_usage(usage: &Value) -> Option<UsageMetrics> {
+    let input_tokens = usage["input_tokens"].as_u64()? as u32;
+    let output_tokens = usage["output_tokens"].as_u64()? as u32;
+    let cache_creation_tokens = usage["cache_creation_input_tokens"]
+        .as_u64()
+        .map(|t| t as u32);
+    let cache_read_tokens = usage["cache_read_input_tokens"].as_u64().map(|t| t as u32);
+
+    let prompt_tokens =
+        input_tokens + cache_creation_tokens.unwrap_or(0) + cache_read_tokens.unwrap_or(0);
+
+    Some(UsageMetrics {
+        prompt_tokens,
+        completion_tokens: output_tokens,
+        total_tokens: prompt_tokens + output_tokens,
+        reasoning_tokens: None,
+        cache_creation_tokens,
+        cache_read_tokens,
+    })
+}
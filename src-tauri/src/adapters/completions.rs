@@ -0,0 +1,215 @@
+//! Legacy text completions (`/completions`) for base models and for testing
+//! without a chat template
+
+use super::{ProviderAdapter, StreamDelta};
+use crate::types::{
+    ApiStyle, ContentPart, Endpoint, LLMRequest, LLMResponse, MessageContent, PerformanceMetrics,
+    TokenLogprob, TopLogprob,
+};
+use serde_json::Value;
+
+pub struct TextCompletions;
+
+impl ProviderAdapter for TextCompletions {
+    fn name(&self) -> &'static str {
+        "completions"
+    }
+
+    fn api_style(&self) -> ApiStyle {
+        ApiStyle::Completions
+    }
+
+    fn generation_url(&self, endpoint: &Endpoint, _request: &LLMRequest, _stream: bool) -> String {
+        format!("{}/completions", endpoint.url)
+    }
+
+    fn build_body(&self, request: &LLMRequest, stream: bool) -> Result<Value, String> {
+        let mut body = serde_json::json!({
+            "model": request.model.clone(),
+            "prompt": prompt(request)?,
+            "temperature": request.temperature,
+            "max_tokens": request.max_tokens,
+            "stream": stream
+        });
+
+        if request.echo {
+            body["echo"] = serde_json::json!(true);
+        }
+        if let Some(suffix) = &request.suffix {
+            body["suffix"] = serde_json::json!(suffix);
+        }
+        if request.logprobs {
+            // The legacy API takes the number of alternatives in `logprobs` itself
+            body["logprobs"] = serde_json::json!(request.top_logprobs.unwrap_or(0));
+        }
+        if let Some(response_format) = &request.response_format {
+            // Not part of the OpenAI API, but vLLM and SGLang apply it to completions too
+            body["response_format"] = serde_json::json!(response_format);
+        }
+
+        if stream {
+            body["stream_options"] = serde_json::json!({ "include_usage": true });
+        }
+
+        Ok(body)
+    }
+
+    fn parse_response(&self, parsed: &Value) -> Result<LLMResponse, String> {
+        let choice = &parsed["choices"][0];
+
+        Ok(LLMResponse {
+            content: choice["text"].as_str().unwrap_or("").to_string(),
+            usage: self.parse_usage(&parsed["usage"]),
+            finish_reason: choice["finish_reason"]
+                .as_str()
+                .unwrap_or("stop")
+                .to_string(),
+            reasoning_content: None,
+            thinking_blocks: Vec::new(),
+            reasoning_provider: None,
+            cancelled: false,
+            metrics: PerformanceMetrics::default(),
+            tool_calls: Vec::new(),
+            validation: None,
+            provider_resolution: None,
+            logprobs: parse_logprobs(&choice["logprobs"]),
+            logprob_stats: None,
+            warnings: Vec::new(),
+        })
+    }
+
+    fn parse_stream_event(&self, parsed: &Value) -> Result<StreamDelta, String> {
+        Ok(StreamDelta {
+            content: parsed["choices"][0]["text"].as_str().map(|s| s.to_string()),
+            // Only the final chunk carries usage (with empty `choices`)
+            usage: self.parse_usage(&parsed["usage"]),
+            logprobs: parse_logprobs(&parsed["choices"][0]["logprobs"]),
+            finish_reason: parsed["choices"][0]["finish_reason"]
+                .as_str()
+                .map(|s| s.to_string()),
+            ..Default::default()
+        })
+    }
+}
+
+/// Parse legacy logprobs: parallel `tokens` and `token_logprobs` arrays, with
+/// alternatives as `{token: logprob}` maps. Echoed prompts start with a `null`
+/// logprob (nothing precedes the first token), which is skipped.
+fn parse_logprobs(logprobs: &Value) -> Vec<TokenLogprob> {
+    let (Some(tokens), Some(token_logprobs)) = (
+        logprobs["tokens"].as_array(),
+        logprobs["token_logprobs"].as_array(),
+    ) else {
+        return Vec::new();
+    };
+
+    tokens
+        .iter()
+        .zip(token_logprobs)
+        .enumerate()
+        .filter_map(|(i, (token, logprob))| {
+            let mut top_logprobs: Vec<TopLogprob> = logprobs["top_logprobs"][i]
+                .as_object()
+                .map(|top| {
+                    top.iter()
+                        .filter_map(|(token, logprob)| {
+                            Some(TopLogprob {
+                                token: token.clone(),
+                                logprob: logprob.as_f64()?,
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            top_logprobs.sort_by(|a, b| b.logprob.total_cmp(&a.logprob));
+
+            Some(TokenLogprob {
+                token: token.as_str()?.to_string(),
+                logprob: logprob.as_f64()?,
+                top_logprobs,
+            })
+        })
+        .collect()
+}
+
+/// The request's raw prompt, or its message texts joined by blank lines. Tools and
+/// media have no text completions equivalent, so they are rejected rather than dropped.
+fn prompt(request: &LLMRequest) -> Result<String, String> {
+    if !request.tools.is_empty() {
+        return Err("Text completions do not support tools".to_string());
+    }
+    if let Some(prompt) = &request.prompt {
+        return Ok(prompt.clone());
+    }
+
+    let has_media = request
+        .messages
+        .iter()
+        .any(|message| match &message.content {
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .any(|part| !matches!(part, ContentPart::Text { .. })),
+            MessageContent::Text(_) => false,
+        });
+    if has_media {
+        return Err("Text completions only accept text content".to_string());
+    }
+
+    Ok(request
+        .messages
+        .iter()
+        .map(|message| message.content.text())
+        .collect::<Vec<_>>()
+        .join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_body_raw_prompt() {
+        let mut request: LLMRequest = serde_json::from_value(serde_json::json!({
+            "model": "base",
+            "messages": [
+                {"role": "system", "content": "You are terse."},
+                {"role": "user", "content": "2+2="}
+            ],
+            "temperature": 0.0,
+            "max_tokens": 4,
+            "stream": false,
+            "reasoningConfig": null,
+            "echo": true,
+            "logprobs": true,
+            "topLogprobs": 5
+        }))
+        .unwrap();
+
+        let body = TextCompletions.build_body(&request, true).unwrap();
+        assert_eq!(body["prompt"], "You are terse.\n\n2+2=");
+        assert_eq!(body["echo"], true);
+        assert_eq!(body["logprobs"], 5);
+        assert!(body.get("suffix").is_none());
+
+        // An explicit prompt bypasses the messages entirely
+        request.prompt = Some("<|begin|>2+2=".to_string());
+        let body = TextCompletions.build_body(&request, false).unwrap();
+        assert_eq!(body["prompt"], "<|begin|>2+2=");
+        assert!(body.get("stream_options").is_none());
+    }
+
+    #[test]
+    fn test_parse_echoed_logprobs() {
+        let logprobs = serde_json::json!({
+            "tokens": ["2+2=", " 4"],
+            "token_logprobs": [null, -0.25],
+            "top_logprobs": [null, {" 5": -3.0, " 4": -0.25}]
+        });
+
+        let parsed = parse_logprobs(&logprobs);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].token, " 4");
+        assert_eq!(parsed[0].top_logprobs[0].token, " 4");
+        assert_eq!(parsed[0].top_logprobs[1].logprob, -3.0);
+    }
+}
@@ -0,0 +1,29 @@
+use super::ProviderAdapter;
+use crate::types::{LLMRequest, ReasoningConfig, ReasoningProvider, ThinkingBlock};
+use serde_json::Value;
+
+pub struct DeepSeek;
+
+impl ProviderAdapter for DeepSeek {
+    fn name(&self) -> &'static str {
+        "deepseek"
+    }
+
+    fn reasoning_provider(&self) -> Option<ReasoningProvider> {
+        Some(ReasoningProvider::DeepSeek)
+    }
+
+    fn apply_reasoning(&self, body: &mut Value, _request: &LLMRequest, config: &ReasoningConfig) {
+        // DeepSeek uses thinking parameter
+        body["thinking"] = serde_json::json!({
+            "type": if config.enable_thinking { "enabled" } else { "disabled" }
+        });
+    }
+
+    fn parse_reasoning(&self, message: &Value) -> (Option<String>, Vec<ThinkingBlock>) {
+        (
+            message["reasoning_content"].as_str().map(|s| s.to_string()),
+            Vec::new(),
+        )
+    }
+}
@@ -0,0 +1,467 @@
+//! Google Gemini `generateContent` / `streamGenerateContent` API
+
+use super::{ProviderAdapter, StreamDelta, ToolCallDelta};
+use crate::media::parse_data_url;
+use crate::types::{
+    ApiStyle, ContentPart, Endpoint, FunctionCall, LLMRequest, LLMResponse, Message,
+    MessageContent, PerformanceMetrics, ReasoningProvider, ResponseFormat, ThinkingBlock, ToolCall,
+    UsageMetrics,
+};
+use reqwest::RequestBuilder;
+use serde_json::Value;
+
+pub struct Gemini;
+
+impl ProviderAdapter for Gemini {
+    fn name(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn api_style(&self) -> ApiStyle {
+        ApiStyle::GeminiGenerateContent
+    }
+
+    fn reasoning_provider(&self) -> Option<ReasoningProvider> {
+        Some(ReasoningProvider::Gemini)
+    }
+
+    fn generation_url(&self, endpoint: &Endpoint, request: &LLMRequest, stream: bool) -> String {
+        // Model IDs from `/models` carry a `models/` prefix
+        let model = request
+            .model
+            .strip_prefix("models/")
+            .unwrap_or(&request.model);
+
+        if stream {
+            format!(
+                "{}/models/{}:streamGenerateContent?alt=sse",
+                endpoint.url, model
+            )
+        } else {
+            format!("{}/models/{}:generateContent", endpoint.url, model)
+        }
+    }
+
+    fn apply_headers(&self, req_builder: RequestBuilder, endpoint: &Endpoint) -> RequestBuilder {
+        match &endpoint.api_key {
+            Some(api_key) => req_builder.header("x-goog-api-key", api_key),
+            None => req_builder,
+        }
+    }
+
+    fn build_body(&self, request: &LLMRequest, _stream: bool) -> Result<Value, String> {
+        build_body(request)
+    }
+
+    fn parse_response(&self, parsed: &Value) -> Result<LLMResponse, String> {
+        if let Some(message) = parsed["error"]["message"].as_str() {
+            return Err(format!("Gemini error: {}", message));
+        }
+        Ok(parse_response(parsed))
+    }
+
+    fn parse_stream_event(&self, parsed: &Value) -> Result<StreamDelta, String> {
+        if let Some(message) = parsed["error"]["message"].as_str() {
+            return Err(format!("Stream error: {}", message));
+        }
+
+        // Every chunk is a full GenerateContentResponse with the next slice of parts
+        let mut delta = StreamDelta {
+            usage: parse_usage(&parsed["usageMetadata"]),
+            finish_reason: parsed["candidates"][0]["finishReason"]
+                .as_str()
+                .map(|s| s.to_string()),
+            ..Default::default()
+        };
+        let parts = parsed["candidates"][0]["content"]["parts"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+
+        let mut content = String::new();
+        let mut thinking = String::new();
+        for (index, part) in parts.iter().enumerate() {
+            if let Some(call) = part.get("functionCall") {
+                // Parallel calls arrive together in one chunk, so the part position
+                // identifies each call
+                let call = parse_function_call(call, index);
+                delta.tool_calls.push(ToolCallDelta {
+                    index,
+                    id: Some(call.id),
+                    name: Some(call.function.name),
+                    arguments: call.function.arguments,
+                });
+            } else if let Some(text) = part["text"].as_str() {
+                if part["thought"] == true {
+                    thinking.push_str(text);
+                } else {
+                    content.push_str(text);
+                }
+            }
+        }
+
+        if !content.is_empty() {
+            delta.content = Some(content);
+        }
+        if !thinking.is_empty() {
+            // Thought summaries form a single block
+            delta.thinking = Some((0, thinking));
+        }
+
+        Ok(delta)
+    }
+
+    fn parse_usage(&self, usage: &Value) -> Option<UsageMetrics> {
+        parse_usage(usage)
+    }
+
+    fn parse_model_list(&self, parsed: &Value) -> Option<Vec<Value>> {
+        let models = parsed["models"].as_array()?;
+
+        Some(
+            models
+                .iter()
+                .map(|model| {
+                    let mut entry = model.clone();
+                    let name = model["name"].as_str().unwrap_or_default();
+                    entry["id"] = serde_json::json!(name.strip_prefix("models/").unwrap_or(name));
+                    entry
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Convert message content to Gemini parts; local media is already base64 by now
+fn parts(content: &MessageContent) -> Vec<Value> {
+    let parts = match content {
+        MessageContent::Text(text) => return vec![serde_json::json!({ "text": text })],
+        MessageContent::Parts(parts) => parts,
+    };
+
+    parts
+        .iter()
+        .map(|part| match part {
+            ContentPart::Text { text } => serde_json::json!({ "text": text }),
+            ContentPart::ImageUrl { image_url } => match parse_data_url(&image_url.url) {
+                Some((media_type, data)) => serde_json::json!({
+                    "inlineData": { "mimeType": media_type, "data": data }
+                }),
+                None => serde_json::json!({ "fileData": { "fileUri": image_url.url } }),
+            },
+            ContentPart::InputAudio { input_audio } => serde_json::json!({
+                "inlineData": {
+                    "mimeType": format!("audio/{}", input_audio.format),
+                    "data": input_audio.data
+                }
+            }),
+        })
+        .collect()
+}
+
+/// Convert chat messages to Gemini `contents`: assistant turns use the `model` role,
+/// tool calls become `functionCall` parts and tool results `functionResponse` parts
+fn contents(messages: &[Message]) -> Vec<Value> {
+    let mut contents: Vec<Value> = Vec::new();
+
+    for message in messages.iter().filter(|m| m.role != "system") {
+        if message.role == "tool" {
+            // Gemini matches results to calls by function name, so look it up by ID
+            let name = messages
+                .iter()
+                .flat_map(|m| &m.tool_calls)
+                .find(|call| Some(&call.id) == message.tool_call_id.as_ref())
+                .map(|call| call.function.name.clone())
+                .unwrap_or_default();
+            let text = message.content.text();
+            let response = match serde_json::from_str::<Value>(&text) {
+                Ok(value @ Value::Object(_)) => value,
+                Ok(value) => serde_json::json!({ "result": value }),
+                Err(_) => serde_json::json!({ "result": text }),
+            };
+            let part = serde_json::json!({
+                "functionResponse": { "name": name, "response": response }
+            });
+
+            // Results for parallel calls share a single user turn
+            if let Some(parts) = contents
+                .last_mut()
+                .filter(|last| {
+                    last["role"] == "user" && last["parts"][0].get("functionResponse").is_some()
+                })
+                .and_then(|last| last["parts"].as_array_mut())
+            {
+                parts.push(part);
+            } else {
+                contents.push(serde_json::json!({ "role": "user", "parts": [part] }));
+            }
+            continue;
+        }
+
+        let role = if message.role == "assistant" {
+            "model"
+        } else {
+            "user"
+        };
+        let mut message_parts = if message.content.is_empty() {
+            Vec::new()
+        } else {
+            parts(&message.content)
+        };
+        for call in &message.tool_calls {
+            message_parts.push(serde_json::json!({
+                "functionCall": {
+                    "name": call.function.name,
+                    "args": serde_json::from_str::<Value>(&call.function.arguments)
+                        .unwrap_or_else(|_| serde_json::json!({}))
+                }
+            }));
+        }
+        contents.push(serde_json::json!({ "role": role, "parts": message_parts }));
+    }
+
+    contents
+}
+
+/// Map an OpenAI `tool_choice` to Gemini's `functionCallingConfig`
+fn tool_config(tool_choice: &Value) -> Value {
+    let config = match tool_choice.as_str() {
+        Some("none") => serde_json::json!({ "mode": "NONE" }),
+        Some("required") => serde_json::json!({ "mode": "ANY" }),
+        Some(_) => serde_json::json!({ "mode": "AUTO" }),
+        None => match tool_choice["function"]["name"].as_str() {
+            Some(name) => serde_json::json!({ "mode": "ANY", "allowedFunctionNames": [name] }),
+            None => serde_json::json!({ "mode": "AUTO" }),
+        },
+    };
+    serde_json::json!({ "functionCallingConfig": config })
+}
+
+/// Build a `generateContent` body: system prompts move to `systemInstruction` and
+/// sampling, thinking and output format settings to `generationConfig`
+fn build_body(request: &LLMRequest) -> Result<Value, String> {
+    let system: Vec<String> = request
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.text())
+        .collect();
+
+    let mut generation_config = serde_json::json!({
+        "temperature": request.temperature,
+        "maxOutputTokens": request.max_tokens
+    });
+
+    if let Some(config) = &request.reasoning_config {
+        // A zero budget turns thinking off on models that allow it
+        let budget = if config.enable_thinking {
+            config
+                .thinking_budget_tokens
+                .map(|b| b as i64)
+                .unwrap_or(-1)
+        } else {
+            0
+        };
+        generation_config["thinkingConfig"] = serde_json::json!({
+            "thinkingBudget": budget,
+            "includeThoughts": config.enable_thinking
+        });
+    }
+
+    match &request.response_format {
+        Some(ResponseFormat::JsonObject) => {
+            generation_config["responseMimeType"] = serde_json::json!("application/json");
+        }
+        Some(ResponseFormat::JsonSchema { json_schema }) => {
+            generation_config["responseMimeType"] = serde_json::json!("application/json");
+            generation_config["responseJsonSchema"] = json_schema.schema.clone();
+        }
+        Some(ResponseFormat::Text) | None => {}
+    }
+
+    let mut body = serde_json::json!({
+        "contents": contents(&request.messages),
+        "generationConfig": generation_config
+    });
+
+    if !system.is_empty() {
+        body["systemInstruction"] = serde_json::json!({
+            "parts": [{ "text": system.join("\n\n") }]
+        });
+    }
+
+    if !request.tools.is_empty() {
+        let declarations: Vec<Value> = request
+            .tools
+            .iter()
+            .map(|tool| {
+                // `parametersJsonSchema` takes full JSON Schema, unlike `parameters`
+                let mut declaration = serde_json::json!({ "name": tool.function.name });
+                if let Some(description) = &tool.function.description {
+                    declaration["description"] = serde_json::json!(description);
+                }
+                if let Some(parameters) = &tool.function.parameters {
+                    declaration["parametersJsonSchema"] = parameters.clone();
+                }
+                declaration
+            })
+            .collect();
+        body["tools"] = serde_json::json!([{ "functionDeclarations": declarations }]);
+    }
+    if let Some(tool_choice) = &request.tool_choice {
+        body["toolConfig"] = tool_config(tool_choice);
+    }
+
+    Ok(body)
+}
+
+/// Parse a `functionCall` part. Older models omit the ID, so one is made up from
+/// the part position.
+fn parse_function_call(call: &Value, index: usize) -> ToolCall {
+    ToolCall {
+        id: call["id"]
+            .as_str()
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| format!("call_{}", index)),
+        kind: "function".to_string(),
+        function: FunctionCall {
+            name: call["name"].as_str().unwrap_or_default().to_string(),
+            arguments: call["args"].to_string(),
+        },
+    }
+}
+
+/// Parse a `generateContent` response; thought parts become one thinking block
+fn parse_response(parsed: &Value) -> LLMResponse {
+    let candidate = &parsed["candidates"][0];
+    let parts = candidate["content"]["parts"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+
+    let mut content = String::new();
+    let mut thinking = String::new();
+    let mut tool_calls = Vec::new();
+    for (index, part) in parts.iter().enumerate() {
+        if let Some(call) = part.get("functionCall") {
+            tool_calls.push(parse_function_call(call, index));
+        } else if let Some(text) = part["text"].as_str() {
+            if part["thought"] == true {
+                thinking.push_str(text);
+            } else {
+                content.push_str(text);
+            }
+        }
+    }
+
+    let thinking_blocks = if thinking.is_empty() {
+        Vec::new()
+    } else {
+        vec![ThinkingBlock {
+            content: thinking,
+            summary: None,
+        }]
+    };
+
+    LLMResponse {
+        content,
+        usage: parse_usage(&parsed["usageMetadata"]),
+        finish_reason: candidate["finishReason"]
+            .as_str()
+            .unwrap_or("STOP")
+            .to_string(),
+        reasoning_content: None,
+        thinking_blocks,
+        reasoning_provider: Some(ReasoningProvider::Gemini),
+        cancelled: false,
+        metrics: PerformanceMetrics::default(),
+        tool_calls,
+        validation: None,
+        provider_resolution: None,
+        logprobs: Vec::new(),
+        logprob_stats: None,
+        warnings: Vec::new(),
+    }
+}
+
+/// Map `usageMetadata` to `UsageMetrics`. Gemini counts thoughts separately from
+/// `candidatesTokenCount`, so completion tokens add them back to stay comparable
+/// with OpenAI's count, which includes reasoning.
+fn parse_usage(usage: &Value) -> Option<UsageMetrics> {
+    let prompt_tokens = usage["promptTokenCount"].as_u64()? as u32;
+    let candidates_tokens = usage["candidatesTokenCount"].as_u64().unwrap_or(0) as u32;
+    let thoughts_tokens = usage["thoughtsTokenCount"].as_u64().map(|t| t as u32);
+    let completion_tokens = candidates_tokens + thoughts_tokens.unwrap_or(0);
+
+    Some(UsageMetrics {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: usage["totalTokenCount"]
+            .as_u64()
+            .map(|t| t as u32)
+            .unwrap_or(prompt_tokens + completion_tokens),
+        reasoning_tokens: thoughts_tokens,
+        cache_creation_tokens: None,
+        cache_read_tokens: usage["cachedContentTokenCount"].as_u64().map(|t| t as u32),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_body() {
+        let request: LLMRequest = serde_json::from_value(serde_json::json!({
+            "model": "gemini-2.5-flash",
+            "messages": [
+                {"role": "system", "content": "Be brief"},
+                {"role": "user", "content": "hi"},
+                {"role": "assistant", "content": "hello"}
+            ],
+            "temperature": 0.5,
+            "max_tokens": 64,
+            "stream": true,
+            "reasoningConfig": {"enableThinking": true, "thinkingBudgetTokens": 512}
+        }))
+        .unwrap();
+
+        let body = build_body(&request).unwrap();
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "Be brief");
+        assert_eq!(body["contents"].as_array().unwrap().len(), 2);
+        assert_eq!(body["contents"][1]["role"], "model");
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 64);
+        assert_eq!(
+            body["generationConfig"]["thinkingConfig"]["thinkingBudget"],
+            512
+        );
+    }
+
+    #[test]
+    fn test_stream_thought_parts_and_usage() {
+        let chunk = serde_json::json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [
+                    {"text": "Considering", "thought": true},
+                    {"text": "Answer"}
+                ]}
+            }],
+            "usageMetadata": {
+                "promptTokenCount": 10,
+                "candidatesTokenCount": 4,
+                "thoughtsTokenCount": 20,
+                "totalTokenCount": 34
+            }
+        });
+
+        let delta = Gemini.parse_stream_event(&chunk).unwrap();
+        assert_eq!(delta.content.as_deref(), Some("Answer"));
+        assert_eq!(delta.thinking, Some((0, "Considering".to_string())));
+        assert_eq!(delta.finish_reason, None);
+
+        let usage = delta.usage.unwrap();
+        assert_eq!(usage.completion_tokens, 24);
+        assert_eq!(usage.reasoning_tokens, Some(20));
+        assert_eq!(usage.total_tokens, 34);
+    }
+}
@@ -0,0 +1,224 @@
+//! Provider adapters: everything that differs between backends (URLs, auth, request
+//! bodies, response/stream parsing and usage) lives behind `ProviderAdapter`.
+//! New backends implement the trait in their own module and register in `ADAPTERS`.
+
+pub mod chat;
+mod claude;
+mod completions;
+mod deepseek;
+mod gemini;
+mod openai;
+mod qwen;
+mod responses;
+
+use crate::provider::resolve_provider;
+use crate::types::{
+    ApiStyle, Endpoint, LLMRequest, LLMResponse, ProviderResolution, ReasoningConfig,
+    ReasoningProvider, ThinkingBlock, TokenLogprob, UsageMetrics,
+};
+use reqwest::RequestBuilder;
+use serde_json::Value;
+
+/// Incremental output extracted from one SSE payload
+#[derive(Default)]
+pub struct StreamDelta {
+    pub content: Option<String>,
+    /// Reasoning text from `delta.reasoning_content` (DeepSeek/Qwen)
+    pub reasoning: Option<String>,
+    /// Thinking text and the index of the content block it belongs to (Claude)
+    pub thinking: Option<(u64, String)>,
+    /// Reasoning summary text and the index of the output item it belongs to
+    /// (OpenAI Responses API)
+    pub summary: Option<(u64, String)>,
+    /// Usage reported by this payload (final chunk / Anthropic message events)
+    pub usage: Option<UsageMetrics>,
+    pub tool_calls: Vec<ToolCallDelta>,
+    /// Logprobs of the tokens in this payload
+    pub logprobs: Vec<TokenLogprob>,
+    /// Why generation stopped, on the payload that ends the response
+    pub finish_reason: Option<String>,
+}
+
+/// Fragment of a streamed tool call. The name and ID arrive once; `arguments` is
+/// streamed in pieces that concatenate to the full JSON string.
+pub struct ToolCallDelta {
+    /// `tool_calls[].index` (OpenAI) or the content block index (Anthropic)
+    pub index: usize,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub arguments: String,
+}
+
+/// One backend's wire format. The default methods speak the OpenAI-compatible
+/// `/chat/completions` API, so chat providers only override what they change.
+pub trait ProviderAdapter: Send + Sync {
+    /// Name shown in responses and logs
+    fn name(&self) -> &'static str;
+
+    /// Wire protocol this adapter speaks
+    fn api_style(&self) -> ApiStyle {
+        ApiStyle::ChatCompletions
+    }
+
+    /// Reasoning model family this adapter handles (`None` for plain chat models)
+    fn reasoning_provider(&self) -> Option<ReasoningProvider> {
+        None
+    }
+
+    /// URL of the generation endpoint
+    fn generation_url(&self, endpoint: &Endpoint, _request: &LLMRequest, _stream: bool) -> String {
+        format!("{}/chat/completions", endpoint.url)
+    }
+
+    /// Add authentication and any headers the API requires; the endpoint's custom
+    /// headers are added afterwards
+    fn apply_headers(&self, req_builder: RequestBuilder, endpoint: &Endpoint) -> RequestBuilder {
+        match &endpoint.api_key {
+            Some(api_key) => req_builder.header("Authorization", format!("Bearer {}", api_key)),
+            None => req_builder,
+        }
+    }
+
+    fn build_body(&self, request: &LLMRequest, stream: bool) -> Result<Value, String> {
+        Ok(chat::build_body(self, request, stream))
+    }
+
+    /// Set provider-specific reasoning parameters on a chat completions body that
+    /// already carries `max_tokens`
+    fn apply_reasoning(&self, _body: &mut Value, _request: &LLMRequest, _config: &ReasoningConfig) {
+    }
+
+    fn parse_response(&self, parsed: &Value) -> Result<LLMResponse, String> {
+        Ok(chat::parse_response(self, parsed))
+    }
+
+    /// Reasoning text and thinking blocks from a non-streaming chat `message`
+    fn parse_reasoning(&self, _message: &Value) -> (Option<String>, Vec<ThinkingBlock>) {
+        (None, Vec::new())
+    }
+
+    /// Extract the incremental output from one SSE `data:` payload
+    fn parse_stream_event(&self, parsed: &Value) -> Result<StreamDelta, String> {
+        Ok(chat::parse_stream_event(self, parsed))
+    }
+
+    fn parse_usage(&self, usage: &Value) -> Option<UsageMetrics> {
+        chat::parse_usage(usage)
+    }
+
+    /// Entries of a `/models` response, each with the model name in `id`
+    fn parse_model_list(&self, parsed: &Value) -> Option<Vec<Value>> {
+        parsed["data"].as_array().cloned()
+    }
+}
+
+/// Every known adapter. Resolution prefers an exact (API style, provider) match,
+/// then the style's generic adapter, then any adapter for the style.
+static ADAPTERS: &[&dyn ProviderAdapter] = &[
+    &openai::OpenAi,
+    &deepseek::DeepSeek,
+    &qwen::Qwen,
+    &claude::ClaudeCompatible,
+    &openai::OpenAiCompatible,
+    &claude::Claude,
+    &gemini::Gemini,
+    &responses::OpenAiResponses,
+    &completions::TextCompletions,
+];
+
+/// Adapter for an endpoint and the model being requested, with how its provider was
+/// resolved. `metadata` is the model's `/models` entry, when it has been fetched.
+pub fn resolve_adapter(
+    endpoint: &Endpoint,
+    model: &str,
+    metadata: Option<&Value>,
+) -> (&'static dyn ProviderAdapter, ProviderResolution) {
+    let (provider, source, reason) = resolve_provider(endpoint, model, metadata);
+    let for_style = || {
+        ADAPTERS
+            .iter()
+            .filter(|adapter| adapter.api_style() == endpoint.api_style)
+    };
+
+    let adapter = for_style()
+        .find(|adapter| adapter.reasoning_provider() == provider)
+        .or_else(|| for_style().find(|adapter| adapter.reasoning_provider().is_none()))
+        .or_else(|| for_style().next())
+        .copied()
+        .unwrap_or(&openai::OpenAiCompatible);
+
+    let resolution = ProviderResolution {
+        adapter: adapter.name().to_string(),
+        provider,
+        source,
+        reason,
+    };
+    (adapter, resolution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ProviderSetting;
+
+    fn adapter_name(endpoint: &Endpoint, model: &str) -> &'static str {
+        resolve_adapter(endpoint, model, None).0.name()
+    }
+
+    fn endpoint(api_style: ApiStyle) -> Endpoint {
+        Endpoint {
+            id: "test".to_string(),
+            name: "test".to_string(),
+            url: "http://localhost".to_string(),
+            api_key: None,
+            headers: Vec::new(),
+            model: String::new(),
+            api_style,
+            provider: ProviderSetting::Auto,
+            timeouts: Default::default(),
+            retry: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_chat_adapters() {
+        let chat = endpoint(ApiStyle::ChatCompletions);
+        assert_eq!(adapter_name(&chat, "o3-mini"), "openai");
+        assert_eq!(adapter_name(&chat, "deepseek-r1"), "deepseek");
+        assert_eq!(adapter_name(&chat, "qwen-plus"), "qwen");
+        assert_eq!(adapter_name(&chat, "claude-4-sonnet"), "claude-compatible");
+        assert_eq!(adapter_name(&chat, "llama-3"), "openai-compatible");
+    }
+
+    #[test]
+    fn test_resolve_anthropic_adapter() {
+        // Any model on a Messages API endpoint is spoken to natively, aliases included
+        let anthropic = endpoint(ApiStyle::AnthropicMessages);
+        assert_eq!(adapter_name(&anthropic, "claude-4-sonnet"), "claude");
+        assert_eq!(adapter_name(&anthropic, "my-alias"), "claude");
+
+        let gemini = endpoint(ApiStyle::GeminiGenerateContent);
+        assert_eq!(adapter_name(&gemini, "gemini-2.0-flash"), "gemini");
+
+        // gpt-4o has no reasoning provider, yet the style's only adapter still applies
+        let responses = endpoint(ApiStyle::Responses);
+        assert_eq!(adapter_name(&responses, "gpt-4o"), "openai-responses");
+
+        // A reasoning model name does not pull a completions endpoint off its style
+        let completions = endpoint(ApiStyle::Completions);
+        assert_eq!(adapter_name(&completions, "deepseek-r1"), "completions");
+    }
+
+    #[test]
+    fn test_resolve_explicit_provider() {
+        let mut chat = endpoint(ApiStyle::ChatCompletions);
+        chat.provider = ProviderSetting::DeepSeek;
+        assert_eq!(adapter_name(&chat, "my-r1-distill"), "deepseek");
+
+        chat.provider = ProviderSetting::Generic;
+        let (adapter, resolution) = resolve_adapter(&chat, "o3-mini", None);
+        assert_eq!(adapter.name(), "openai-compatible");
+        assert_eq!(resolution.adapter, "openai-compatible");
+        assert_eq!(resolution.provider, None);
+    }
+}
@@ -0,0 +1,39 @@
+use super::ProviderAdapter;
+use crate::types::{LLMRequest, ReasoningConfig, ReasoningProvider};
+use serde_json::Value;
+
+/// Any OpenAI-compatible server (vLLM, Ollama, gateways, ...) with no reasoning extras
+pub struct OpenAiCompatible;
+
+impl ProviderAdapter for OpenAiCompatible {
+    fn name(&self) -> &'static str {
+        "openai-compatible"
+    }
+}
+
+/// OpenAI reasoning models (o1, o3, gpt-5)
+pub struct OpenAi;
+
+impl ProviderAdapter for OpenAi {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn reasoning_provider(&self) -> Option<ReasoningProvider> {
+        Some(ReasoningProvider::OpenAI)
+    }
+
+    fn apply_reasoning(&self, body: &mut Value, _request: &LLMRequest, config: &ReasoningConfig) {
+        // OpenAI uses max_completion_tokens for reasoning models
+        if let Some(max_completion) = config.max_completion_tokens {
+            if let Some(body) = body.as_object_mut() {
+                body.remove("max_tokens");
+            }
+            body["max_completion_tokens"] = serde_json::json!(max_completion);
+        }
+
+        if let Some(effort) = &config.reasoning_effort {
+            body["reasoning_effort"] = serde_json::json!(effort);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use super::ProviderAdapter;
+use crate::types::{LLMRequest, ReasoningConfig, ReasoningProvider, ThinkingBlock};
+use serde_json::Value;
+
+pub struct Qwen;
+
+impl ProviderAdapter for Qwen {
+    fn name(&self) -> &'static str {
+        "qwen"
+    }
+
+    fn reasoning_provider(&self) -> Option<ReasoningProvider> {
+        Some(ReasoningProvider::Qwen)
+    }
+
+    fn apply_reasoning(&self, body: &mut Value, _request: &LLMRequest, config: &ReasoningConfig) {
+        // Qwen uses enable_thinking and thinking_budget
+        if config.enable_thinking {
+            body["enable_thinking"] = serde_json::json!(true);
+            if let Some(budget) = config.thinking_budget_tokens {
+                body["thinking_budget"] = serde_json::json!(budget);
+            }
+        }
+    }
+
+    fn parse_reasoning(&self, message: &Value) -> (Option<String>, Vec<ThinkingBlock>) {
+        (
+            message["reasoning_content"].as_str().map(|s| s.to_string()),
+            Vec::new(),
+        )
+    }
+}
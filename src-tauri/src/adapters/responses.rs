@@ -0,0 +1,457 @@
+//! OpenAI Responses API (`/responses`), the only OpenAI API that returns reasoning
+//! summaries
+
+use super::{chat, ProviderAdapter, StreamDelta, ToolCallDelta};
+use crate::types::{
+    ApiStyle, ContentPart, Endpoint, FunctionCall, LLMRequest, LLMResponse, Message,
+    MessageContent, PerformanceMetrics, ReasoningProvider, ResponseFormat, ThinkingBlock, ToolCall,
+    UsageMetrics,
+};
+use serde_json::Value;
+
+pub struct OpenAiResponses;
+
+impl ProviderAdapter for OpenAiResponses {
+    fn name(&self) -> &'static str {
+        "openai-responses"
+    }
+
+    fn api_style(&self) -> ApiStyle {
+        ApiStyle::Responses
+    }
+
+    fn reasoning_provider(&self) -> Option<ReasoningProvider> {
+        Some(ReasoningProvider::OpenAI)
+    }
+
+    fn generation_url(&self, endpoint: &Endpoint, _request: &LLMRequest, _stream: bool) -> String {
+        format!("{}/responses", endpoint.url)
+    }
+
+    fn build_body(&self, request: &LLMRequest, stream: bool) -> Result<Value, String> {
+        build_body(request, stream)
+    }
+
+    fn parse_response(&self, parsed: &Value) -> Result<LLMResponse, String> {
+        if let Some(message) = parsed["error"]["message"].as_str() {
+            return Err(format!("Response failed: {}", message));
+        }
+        Ok(parse_response(parsed))
+    }
+
+    fn parse_stream_event(&self, parsed: &Value) -> Result<StreamDelta, String> {
+        let output_index = parsed["output_index"].as_u64().unwrap_or(0);
+
+        match parsed["type"].as_str() {
+            Some("error") => Err(format!(
+                "Stream error: {}",
+                parsed["message"].as_str().unwrap_or("Unknown error")
+            )),
+            Some("response.failed") => Err(format!(
+                "Stream error: {}",
+                parsed["response"]["error"]["message"]
+                    .as_str()
+                    .unwrap_or("Unknown error")
+            )),
+            Some("response.output_text.delta") => Ok(StreamDelta {
+                content: parsed["delta"].as_str().map(|s| s.to_string()),
+                logprobs: chat::parse_logprobs(&parsed["logprobs"]),
+                ..Default::default()
+            }),
+            Some("response.reasoning_summary_text.delta") => Ok(StreamDelta {
+                summary: parsed["delta"]
+                    .as_str()
+                    .map(|s| (output_index, s.to_string())),
+                ..Default::default()
+            }),
+            // Later summary parts of the same reasoning item are separated like paragraphs
+            Some("response.reasoning_summary_part.added")
+                if parsed["summary_index"].as_u64().unwrap_or(0) > 0 =>
+            {
+                Ok(StreamDelta {
+                    summary: Some((output_index, "\n\n".to_string())),
+                    ..Default::default()
+                })
+            }
+            // A function call item opens with its call ID and name; arguments follow as deltas
+            Some("response.output_item.added") if parsed["item"]["type"] == "function_call" => {
+                let item = &parsed["item"];
+                Ok(StreamDelta {
+                    tool_calls: vec![ToolCallDelta {
+                        index: output_index as usize,
+                        id: item["call_id"].as_str().map(|s| s.to_string()),
+                        name: item["name"].as_str().map(|s| s.to_string()),
+                        arguments: item["arguments"].as_str().unwrap_or_default().to_string(),
+                    }],
+                    ..Default::default()
+                })
+            }
+            Some("response.function_call_arguments.delta") => Ok(StreamDelta {
+                tool_calls: vec![ToolCallDelta {
+                    index: output_index as usize,
+                    id: None,
+                    name: None,
+                    arguments: parsed["delta"].as_str().unwrap_or_default().to_string(),
+                }],
+                ..Default::default()
+            }),
+            // Usage only arrives with the final response
+            Some("response.completed") | Some("response.incomplete") => {
+                let response = &parsed["response"];
+                Ok(StreamDelta {
+                    usage: parse_usage(&response["usage"]),
+                    finish_reason: response["incomplete_details"]["reason"]
+                        .as_str()
+                        .or_else(|| response["status"].as_str())
+                        .map(|s| s.to_string()),
+                    ..Default::default()
+                })
+            }
+            // response.created, output_item.done, content_part.added, ...
+            _ => Ok(StreamDelta::default()),
+        }
+    }
+
+    fn parse_usage(&self, usage: &Value) -> Option<UsageMetrics> {
+        parse_usage(usage)
+    }
+}
+
+/// Convert message content to input content; plain strings pass through
+fn content(content: &MessageContent) -> Result<Value, String> {
+    let parts = match content {
+        MessageContent::Text(text) => return Ok(serde_json::json!(text)),
+        MessageContent::Parts(parts) => parts,
+    };
+
+    let parts = parts
+        .iter()
+        .map(|part| match part {
+            ContentPart::Text { text } => {
+                Ok(serde_json::json!({ "type": "input_text", "text": text }))
+            }
+            ContentPart::ImageUrl { image_url } => {
+                let mut image = serde_json::json!({
+                    "type": "input_image",
+                    "image_url": image_url.url
+                });
+                if let Some(detail) = &image_url.detail {
+                    image["detail"] = serde_json::json!(detail);
+                }
+                Ok(image)
+            }
+            ContentPart::InputAudio { .. } => {
+                Err("The Responses API does not accept audio input".to_string())
+            }
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(Value::Array(parts))
+}
+
+/// Convert chat messages to `input` items: tool calls become `function_call` items
+/// and tool results `function_call_output` items
+fn input(messages: &[Message]) -> Result<Vec<Value>, String> {
+    let mut items = Vec::new();
+
+    for message in messages.iter().filter(|m| m.role != "system") {
+        if message.role == "tool" {
+            items.push(serde_json::json!({
+                "type": "function_call_output",
+                "call_id": message.tool_call_id.clone().unwrap_or_default(),
+                "output": message.content.text()
+            }));
+            continue;
+        }
+
+        if !message.content.is_empty() || message.tool_calls.is_empty() {
+            items.push(serde_json::json!({
+                "role": message.role,
+                "content": content(&message.content)?
+            }));
+        }
+        for call in &message.tool_calls {
+            items.push(serde_json::json!({
+                "type": "function_call",
+                "call_id": call.id,
+                "name": call.function.name,
+                "arguments": call.function.arguments
+            }));
+        }
+    }
+
+    Ok(items)
+}
+
+/// Map an OpenAI chat `tool_choice` to the Responses API's flatter form
+fn tool_choice(tool_choice: &Value) -> Value {
+    match tool_choice["function"]["name"].as_str() {
+        Some(name) => serde_json::json!({ "type": "function", "name": name }),
+        None => tool_choice.clone(),
+    }
+}
+
+/// Build a `/responses` body: system prompts become `instructions`, and reasoning
+/// settings `reasoning: {effort, summary}`
+fn build_body(request: &LLMRequest, stream: bool) -> Result<Value, String> {
+    let system: Vec<String> = request
+        .messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(|m| m.content.text())
+        .collect();
+
+    let mut body = serde_json::json!({
+        "model": request.model.clone(),
+        "input": input(&request.messages)?,
+        "max_output_tokens": request.max_tokens,
+        "stream": stream
+    });
+
+    if !system.is_empty() {
+        body["instructions"] = serde_json::json!(system.join("\n\n"));
+    }
+
+    if let Some(config) = &request.reasoning_config {
+        let mut reasoning = serde_json::json!({ "summary": "auto" });
+        if let Some(effort) = &config.reasoning_effort {
+            reasoning["effort"] = serde_json::json!(effort);
+        }
+        body["reasoning"] = reasoning;
+
+        if let Some(max_completion) = config.max_completion_tokens {
+            body["max_output_tokens"] = serde_json::json!(max_completion);
+        }
+    } else {
+        // Reasoning models reject a custom temperature
+        body["temperature"] = serde_json::json!(request.temperature);
+    }
+
+    if !request.tools.is_empty() {
+        let tools: Vec<Value> = request
+            .tools
+            .iter()
+            .map(|tool| {
+                let mut converted = serde_json::json!({
+                    "type": "function",
+                    "name": tool.function.name,
+                    "parameters": tool
+                        .function
+                        .parameters
+                        .clone()
+                        .unwrap_or_else(|| serde_json::json!({ "type": "object" }))
+                });
+                if let Some(description) = &tool.function.description {
+                    converted["description"] = serde_json::json!(description);
+                }
+                converted
+            })
+            .collect();
+        body["tools"] = serde_json::json!(tools);
+    }
+    if let Some(choice) = &request.tool_choice {
+        body["tool_choice"] = tool_choice(choice);
+    }
+
+    match &request.response_format {
+        Some(ResponseFormat::JsonObject) => {
+            body["text"] = serde_json::json!({ "format": { "type": "json_object" } });
+        }
+        Some(ResponseFormat::JsonSchema { json_schema }) => {
+            let mut format = serde_json::json!({
+                "type": "json_schema",
+                "name": json_schema.name,
+                "schema": json_schema.schema
+            });
+            if let Some(strict) = json_schema.strict {
+                format["strict"] = serde_json::json!(strict);
+            }
+            body["text"] = serde_json::json!({ "format": format });
+        }
+        Some(ResponseFormat::Text) | None => {}
+    }
+
+    if request.logprobs {
+        body["include"] = serde_json::json!(["message.output_text.logprobs"]);
+        if let Some(top_logprobs) = request.top_logprobs {
+            body["top_logprobs"] = serde_json::json!(top_logprobs);
+        }
+    }
+
+    Ok(body)
+}
+
+/// Parse a `/responses` result made of typed output items. Each reasoning item
+/// becomes a thinking block whose summary holds its summary parts.
+fn parse_response(parsed: &Value) -> LLMResponse {
+    let items = parsed["output"].as_array().cloned().unwrap_or_default();
+
+    let text_parts: Vec<&Value> = items
+        .iter()
+        .filter(|item| item["type"] == "message")
+        .filter_map(|item| item["content"].as_array())
+        .flatten()
+        .filter(|part| part["type"] == "output_text")
+        .collect();
+    let content = text_parts
+        .iter()
+        .filter_map(|part| part["text"].as_str())
+        .collect::<String>();
+
+    let thinking_blocks = items
+        .iter()
+        .filter(|item| item["type"] == "reasoning")
+        .map(|item| {
+            let summary = item["summary"]
+                .as_array()
+                .map(|parts| {
+                    parts
+                        .iter()
+                        .filter_map(|part| part["text"].as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n\n")
+                })
+                .filter(|summary| !summary.is_empty());
+            ThinkingBlock {
+                content: String::new(),
+                summary,
+            }
+        })
+        .collect();
+
+    let tool_calls = items
+        .iter()
+        .filter(|item| item["type"] == "function_call")
+        .map(|item| ToolCall {
+            id: item["call_id"].as_str().unwrap_or_default().to_string(),
+            kind: "function".to_string(),
+            function: FunctionCall {
+                name: item["name"].as_str().unwrap_or_default().to_string(),
+                arguments: item["arguments"].as_str().unwrap_or_default().to_string(),
+            },
+        })
+        .collect();
+
+    LLMResponse {
+        content,
+        usage: parse_usage(&parsed["usage"]),
+        // `incomplete_details.reason` says why an incomplete response stopped
+        finish_reason: parsed["incomplete_details"]["reason"]
+            .as_str()
+            .or_else(|| parsed["status"].as_str())
+            .unwrap_or("completed")
+            .to_string(),
+        reasoning_content: None,
+        thinking_blocks,
+        reasoning_provider: Some(ReasoningProvider::OpenAI),
+        cancelled: false,
+        metrics: PerformanceMetrics::default(),
+        tool_calls,
+        validation: None,
+        provider_resolution: None,
+        logprobs: text_parts
+            .iter()
+            .flat_map(|part| chat::parse_logprobs(&part["logprobs"]))
+            .collect(),
+        logprob_stats: None,
+        warnings: Vec::new(),
+    }
+}
+
+/// Map Responses API usage (`input_tokens`/`output_tokens` with their details)
+/// to `UsageMetrics`
+fn parse_usage(usage: &Value) -> Option<UsageMetrics> {
+    let prompt_tokens = usage["input_tokens"].as_u64()? as u32;
+    let completion_tokens = usage["output_tokens"].as_u64()? as u32;
+
+    Some(UsageMetrics {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: usage["total_tokens"]
+            .as_u64()
+            .map(|t| t as u32)
+            .unwrap_or(prompt_tokens + completion_tokens),
+        reasoning_tokens: usage["output_tokens_details"]["reasoning_tokens"]
+            .as_u64()
+            .map(|t| t as u32),
+        cache_creation_tokens: None,
+        cache_read_tokens: usage["input_tokens_details"]["cached_tokens"]
+            .as_u64()
+            .map(|t| t as u32),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response_summaries_and_usage() {
+        let parsed = serde_json::json!({
+            "status": "completed",
+            "output": [
+                {"type": "reasoning", "summary": [
+                    {"type": "summary_text", "text": "First"},
+                    {"type": "summary_text", "text": "Second"}
+                ]},
+                {"type": "message", "role": "assistant", "content": [
+                    {"type": "output_text", "text": "4"}
+                ]}
+            ],
+            "usage": {
+                "input_tokens": 12,
+                "input_tokens_details": {"cached_tokens": 8},
+                "output_tokens": 40,
+                "output_tokens_details": {"reasoning_tokens": 32},
+                "total_tokens": 52
+            }
+        });
+
+        let response = parse_response(&parsed);
+        assert_eq!(response.content, "4");
+        assert_eq!(response.finish_reason, "completed");
+        assert_eq!(
+            response.thinking_blocks[0].summary.as_deref(),
+            Some("First\n\nSecond")
+        );
+
+        let usage = response.usage.unwrap();
+        assert_eq!(usage.reasoning_tokens, Some(32));
+        assert_eq!(usage.cache_read_tokens, Some(8));
+    }
+
+    #[test]
+    fn test_stream_events() {
+        let summary = serde_json::json!({
+            "type": "response.reasoning_summary_text.delta",
+            "output_index": 0,
+            "summary_index": 0,
+            "delta": "Adding"
+        });
+        let delta = OpenAiResponses.parse_stream_event(&summary).unwrap();
+        assert_eq!(delta.summary, Some((0, "Adding".to_string())));
+
+        let text = serde_json::json!({
+            "type": "response.output_text.delta",
+            "output_index": 1,
+            "delta": "4"
+        });
+        let delta = OpenAiResponses.parse_stream_event(&text).unwrap();
+        assert_eq!(delta.content.as_deref(), Some("4"));
+
+        let completed = serde_json::json!({
+            "type": "response.completed",
+            "response": {"status": "completed", "usage": {"input_tokens": 3, "output_tokens": 5, "total_tokens": 8}}
+        });
+        let delta = OpenAiResponses.parse_stream_event(&completed).unwrap();
+        assert_eq!(delta.finish_reason.as_deref(), Some("completed"));
+        assert_eq!(delta.usage.unwrap().completion_tokens, 5);
+
+        let incomplete = serde_json::json!({
+            "type": "response.incomplete",
+            "response": {"status": "incomplete", "incomplete_details": {"reason": "max_output_tokens"}}
+        });
+        let delta = OpenAiResponses.parse_stream_event(&incomplete).unwrap();
+        assert_eq!(delta.finish_reason.as_deref(), Some("max_output_tokens"));
+    }
+}
@@ -0,0 +1,384 @@
+use crate::cancel::CancelToken;
+use crate::client::HttpClient;
+use crate::http::{reasoning_text, send_llm_request, send_llm_request_streaming, NullSink};
+use crate::metrics::{as_ms, failed_request_metrics, latency_stats};
+use crate::types::{
+    BatchConfig, BatchProgress, BatchResult, BatchSummary, Endpoint, LLMRequest, Message,
+    PerformanceMetrics, ReasoningConfig,
+};
+use futures_util::StreamExt;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// `max_tokens` for raw OpenAI bodies that set no limit, matching the app default
+const DEFAULT_MAX_TOKENS: u32 = 2048;
+
+/// Receives each line's result as it is written
+pub trait BatchSink: Send + Sync {
+    fn on_result(&self, progress: &BatchProgress) -> Result<(), String>;
+}
+
+impl BatchSink for tauri::AppHandle {
+    fn on_result(&self, progress: &BatchProgress) -> Result<(), String> {
+        use tauri::Manager;
+
+        self.emit_all("batch_progress", progress)
+            .map_err(|e| format!("Failed to emit batch progress: {}", e))
+    }
+}
+
+/// `evals.jsonl` -> `evals.results.jsonl`, next to the input
+pub fn default_output_path(input: &Path) -> PathBuf {
+    input.with_extension("results.jsonl")
+}
+
+/// Parse an input line as an `LLMRequest` or a raw OpenAI (chat) completions body,
+/// optionally wrapped in an OpenAI Batch API envelope (`custom_id` + `body`)
+fn parse_line(text: &str) -> (Option<String>, Result<LLMRequest, String>) {
+    let value: Value = match serde_json::from_str(text) {
+        Ok(value) => value,
+        Err(e) => return (None, Err(format!("Invalid JSON: {}", e))),
+    };
+
+    let custom_id = value
+        .get("custom_id")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let body = value
+        .get("body")
+        .filter(|b| b.is_object())
+        .unwrap_or(&value);
+
+    let request =
+        serde_json::from_value::<LLMRequest>(body.clone()).or_else(|_| from_openai_body(body));
+
+    (custom_id, request)
+}
+
+/// Convert a raw OpenAI body, filling in the fields it may omit. Legacy completions
+/// bodies carry a `prompt` instead of `messages`.
+fn from_openai_body(body: &Value) -> Result<LLMRequest, String> {
+    let prompt = body
+        .get("prompt")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let messages: Vec<Message> = match body.get("messages") {
+        Some(messages) => serde_json::from_value(messages.clone())
+            .map_err(|e| format!("Invalid messages: {}", e))?,
+        None if prompt.is_some() => Vec::new(),
+        None => return Err("Line has no messages or prompt".to_string()),
+    };
+
+    let max_completion_tokens = body
+        .get("max_completion_tokens")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32);
+    let reasoning_effort = body
+        .get("reasoning_effort")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let wants_reasoning = reasoning_effort.is_some() || max_completion_tokens.is_some();
+
+    Ok(LLMRequest {
+        model: body
+            .get("model")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        messages,
+        temperature: body
+            .get("temperature")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0) as f32,
+        max_tokens: body
+            .get("max_tokens")
+            .and_then(Value::as_u64)
+            .map(|v| v as u32)
+            .or(max_completion_tokens)
+            .unwrap_or(DEFAULT_MAX_TOKENS),
+        stream: body.get("stream").and_then(Value::as_bool).unwrap_or(false),
+        tools: match body.get("tools") {
+            Some(tools) => serde_json::from_value(tools.clone())
+                .map_err(|e| format!("Invalid tools: {}", e))?,
+            None => Vec::new(),
+        },
+        tool_choice: body.get("tool_choice").cloned(),
+        response_format: match body.get("response_format") {
+            Some(format) => Some(
+                serde_json::from_value(format.clone())
+                    .map_err(|e| format!("Invalid response_format: {}", e))?,
+            ),
+            None => None,
+        },
+        prompt,
+        echo: body.get("echo").and_then(Value::as_bool).unwrap_or(false),
+        suffix: body
+            .get("suffix")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        // `true` on chat completions; the number of alternatives on legacy completions
+        logprobs: match body.get("logprobs") {
+            Some(Value::Bool(logprobs)) => *logprobs,
+            Some(value) => value.is_u64(),
+            None => false,
+        },
+        top_logprobs: body
+            .get("top_logprobs")
+            .or_else(|| body.get("logprobs").filter(|v| v.is_u64()))
+            .and_then(Value::as_u64)
+            .map(|v| v as u32),
+        reasoning_config: wants_reasoning.then_some(ReasoningConfig {
+            enable_thinking: false,
+            reasoning_effort,
+            max_completion_tokens,
+            thinking_budget_tokens: None,
+        }),
+    })
+}
+
+/// Replay every non-empty line of `input` against `endpoint`, writing one result per
+/// line to `output` in input order. The endpoint's model replaces each line's model
+/// (unless it is empty) so the same file can be run against every server.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_batch(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    input: &Path,
+    output: &Path,
+    config: &BatchConfig,
+    batch_id: &str,
+    cancel: &CancelToken,
+    sink: &dyn BatchSink,
+) -> Result<BatchSummary, String> {
+    if config.concurrency == 0 {
+        return Err("Concurrency must be at least 1".to_string());
+    }
+
+    let content = fs::read_to_string(input)
+        .map_err(|e| format!("Failed to read {}: {}", input.display(), e))?;
+    // Owned lines, so the replay futures borrow nothing from `content` and the command
+    // future stays `Send`
+    let lines: Vec<(usize, String)> = content
+        .lines()
+        .enumerate()
+        .filter(|(_, text)| !text.trim().is_empty())
+        .map(|(index, text)| (index + 1, text.to_string()))
+        .collect();
+    let total = lines.len();
+
+    let mut writer = BufWriter::new(
+        File::create(output)
+            .map_err(|e| format!("Failed to create {}: {}", output.display(), e))?,
+    );
+
+    let start = Instant::now();
+    let mut results = futures_util::stream::iter(lines)
+        .map(|(line, text)| async move {
+            replay_line(http, endpoint, line, &text, config, batch_id, cancel).await
+        })
+        .buffered(config.concurrency);
+
+    let mut completed = 0;
+    let mut succeeded = 0;
+    let mut invalid_outputs = 0;
+    let mut ttfts = Vec::new();
+    let mut latencies = Vec::new();
+
+    while let Some(result) = results.next().await {
+        let Some(result) = result else {
+            continue;
+        };
+
+        let json = serde_json::to_string(&result)
+            .map_err(|e| format!("Failed to serialize result: {}", e))?;
+        // Flush per line so an interrupted batch still leaves usable results
+        writeln!(writer, "{}", json)
+            .and_then(|_| writer.flush())
+            .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+
+        completed += 1;
+        if result.validation.as_ref().is_some_and(|v| !v.valid) {
+            invalid_outputs += 1;
+        }
+        if result.success {
+            succeeded += 1;
+            ttfts.push(result.metrics.ttft_ms);
+            latencies.push(result.metrics.total_latency_ms);
+        }
+
+        sink.on_result(&BatchProgress {
+            batch_id: batch_id.to_string(),
+            completed,
+            total,
+            result,
+        })?;
+    }
+
+    Ok(BatchSummary {
+        batch_id: batch_id.to_string(),
+        input_path: input.display().to_string(),
+        output_path: output.display().to_string(),
+        total,
+        completed,
+        succeeded,
+        failed: completed - succeeded,
+        invalid_outputs,
+        cancelled: cancel.is_cancelled(),
+        duration_ms: as_ms(start.elapsed()),
+        ttft: latency_stats(&ttfts),
+        latency: latency_stats(&latencies),
+    })
+}
+
+/// Send one line; `None` if the batch was cancelled before it completed
+async fn replay_line(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    line: usize,
+    text: &str,
+    config: &BatchConfig,
+    batch_id: &str,
+    cancel: &CancelToken,
+) -> Option<BatchResult> {
+    if cancel.is_cancelled() {
+        return None;
+    }
+
+    let (custom_id, request) = parse_line(text);
+    let failed = |error: String, metrics: PerformanceMetrics| BatchResult {
+        line,
+        custom_id: custom_id.clone(),
+        success: false,
+        error: Some(error),
+        content: String::new(),
+        reasoning_content: None,
+        finish_reason: None,
+        tool_calls: Vec::new(),
+        usage: None,
+        metrics,
+        validation: None,
+    };
+
+    let mut request = match request {
+        Ok(request) => request,
+        Err(e) => return Some(failed(e, PerformanceMetrics::default())),
+    };
+    if !endpoint.model.is_empty() {
+        request.model = endpoint.model.clone();
+    }
+    if request.model.is_empty() {
+        return Some(failed(
+            "No model on the line or the endpoint".to_string(),
+            PerformanceMetrics::default(),
+        ));
+    }
+    if let Some(stream) = config.stream {
+        request.stream = stream;
+    }
+
+    let start = Instant::now();
+
+    if request.stream {
+        let request_id = format!("{}-{}", batch_id, line);
+        match send_llm_request_streaming(http, endpoint, &request, &request_id, cancel, &NullSink)
+            .await
+        {
+            Ok(streamed) if streamed.cancelled => None,
+            Ok(streamed) => Some(BatchResult {
+                line,
+                custom_id: custom_id.clone(),
+                success: true,
+                error: None,
+                content: streamed.content,
+                reasoning_content: reasoning_text(
+                    streamed.reasoning_content,
+                    &streamed.thinking_blocks,
+                ),
+                finish_reason: streamed.finish_reason,
+                tool_calls: streamed.tool_calls,
+                usage: streamed.usage,
+                metrics: streamed.metrics,
+                validation: streamed.validation,
+            }),
+            Err(e) => Some(failed(
+                e.to_string(),
+                failed_request_metrics(start.elapsed(), &e),
+            )),
+        }
+    } else {
+        match send_llm_request(http, endpoint, &request, cancel).await {
+            Ok(response) if response.cancelled => None,
+            Ok(response) => Some(BatchResult {
+                line,
+                custom_id: custom_id.clone(),
+                success: true,
+                error: None,
+                content: response.content,
+                reasoning_content: reasoning_text(
+                    response.reasoning_content,
+                    &response.thinking_blocks,
+                ),
+                finish_reason: Some(response.finish_reason),
+                tool_calls: response.tool_calls,
+                usage: response.usage,
+                metrics: response.metrics,
+                validation: response.validation,
+            }),
+            Err(e) => Some(failed(
+                e.to_string(),
+                failed_request_metrics(start.elapsed(), &e),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_llm_request_line() {
+        let line = r#"{"model":"m","messages":[{"role":"user","content":"hi"}],"temperature":0.5,"maxTokens":64,"stream":true,"reasoningConfig":null}"#;
+        let (custom_id, request) = parse_line(line);
+        let request = request.unwrap();
+        assert!(custom_id.is_none());
+        assert_eq!(request.max_tokens, 64);
+        assert!(request.stream);
+    }
+
+    #[test]
+    fn test_parse_raw_openai_body() {
+        let line = r#"{"model":"gpt-4o","messages":[{"role":"user","content":"hi"}],"max_completion_tokens":128,"reasoning_effort":"low"}"#;
+        let request = parse_line(line).1.unwrap();
+        assert_eq!(request.model, "gpt-4o");
+        assert_eq!(request.temperature, 0.0);
+        assert_eq!(request.max_tokens, 128);
+        assert!(!request.stream);
+        let reasoning = request.reasoning_config.unwrap();
+        assert_eq!(reasoning.reasoning_effort.as_deref(), Some("low"));
+    }
+
+    #[test]
+    fn test_parse_batch_api_envelope() {
+        let line = r#"{"custom_id":"q-1","method":"POST","url":"/v1/chat/completions","body":{"model":"m","messages":[{"role":"user","content":"hi"}]}}"#;
+        let (custom_id, request) = parse_line(line);
+        assert_eq!(custom_id.as_deref(), Some("q-1"));
+        assert_eq!(request.unwrap().max_tokens, DEFAULT_MAX_TOKENS);
+
+        let line = r#"{"custom_id":"q-2","method":"POST","url":"/v1/completions","body":{"model":"m","prompt":"2+2=","echo":true,"logprobs":3}}"#;
+        let request = parse_line(line).1.unwrap();
+        assert_eq!(request.prompt.as_deref(), Some("2+2="));
+        assert!(request.echo);
+        assert!(request.logprobs);
+        assert_eq!(request.top_logprobs, Some(3));
+    }
+
+    #[test]
+    fn test_parse_invalid_lines() {
+        assert!(parse_line("not json").1.is_err());
+        assert!(parse_line(r#"{"model":"m"}"#).1.is_err());
+    }
+}
@@ -0,0 +1,550 @@
+use crate::cancel::CancelToken;
+use crate::client::HttpClient;
+use crate::http::{send_llm_request, send_llm_request_streaming, NullSink};
+use crate::media::inline_local_media;
+use crate::metrics::{as_ms, failed_request_metrics, latency_stats};
+use crate::types::{
+    BenchmarkConfig, BenchmarkProgress, BenchmarkReport, BenchmarkSample, Endpoint, ErrorCount,
+    LLMRequest, RetryStats,
+};
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// Receives progress updates as benchmark requests complete
+pub trait ProgressSink: Send + Sync {
+    fn on_progress(&self, progress: &BenchmarkProgress) -> Result<(), String>;
+}
+
+impl ProgressSink for tauri::AppHandle {
+    fn on_progress(&self, progress: &BenchmarkProgress) -> Result<(), String> {
+        use tauri::Manager;
+
+        self.emit_all("benchmark_progress", progress)
+            .map_err(|e| format!("Failed to emit benchmark progress: {}", e))
+    }
+}
+
+fn validate_config(config: &BenchmarkConfig) -> Result<(), String> {
+    if config.total_requests == 0 {
+        return Err("Benchmark needs at least one request".to_string());
+    }
+    if config.concurrency == 0 {
+        return Err("Concurrency must be at least 1".to_string());
+    }
+    if let Some(qps) = config.target_qps {
+        if !(qps > 0.0 && qps.is_finite()) {
+            return Err(format!("Invalid target QPS: {}", qps));
+        }
+    }
+    Ok(())
+}
+
+/// When request `index` is released at a target QPS, relative to the benchmark start
+fn scheduled_offset(index: usize, qps: f64) -> Duration {
+    Duration::from_secs_f64(index as f64 / qps)
+}
+
+/// Send `config.total_requests` copies of `request`, keeping at most `config.concurrency`
+/// in flight. With a target QPS requests are released on a fixed schedule whether or not
+/// earlier ones have finished; a released request that finds every slot taken waits for
+/// one, and that wait is reported as its queueing delay. Requests that are cancelled
+/// mid-flight are left out of the report.
+pub async fn run_benchmark(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    request: &LLMRequest,
+    config: &BenchmarkConfig,
+    benchmark_id: &str,
+    cancel: &CancelToken,
+    progress: &dyn ProgressSink,
+) -> Result<BenchmarkReport, String> {
+    validate_config(config)?;
+
+    // Encode local media once rather than on every request
+    let request = inline_local_media(request)?;
+    let request = request.as_ref();
+
+    let start = Instant::now();
+    let total = config.total_requests;
+
+    let slots = Semaphore::new(config.concurrency);
+    let slots = &slots;
+    // On a schedule every request is waiting for its release time at once
+    let waiting = match config.target_qps {
+        Some(_) => total,
+        None => config.concurrency,
+    };
+
+    let mut pending = futures_util::stream::iter(0..total)
+        .map(|index| async move {
+            let due = match config.target_qps {
+                Some(qps) => {
+                    let due = start + scheduled_offset(index, qps);
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(due.into()) => {}
+                        _ = cancel.cancelled() => return None,
+                    }
+                    Some(due)
+                }
+                None => None,
+            };
+
+            let _slot = tokio::select! {
+                slot = slots.acquire() => slot.ok()?,
+                _ = cancel.cancelled() => return None,
+            };
+
+            if cancel.is_cancelled() {
+                return None;
+            }
+
+            let queue_ms = due.map(|due| as_ms(due.elapsed()));
+            run_sample(
+                http,
+                endpoint,
+                request,
+                benchmark_id,
+                index,
+                start,
+                queue_ms,
+                cancel,
+            )
+            .await
+        })
+        .buffer_unordered(waiting);
+
+    let mut samples = Vec::with_capacity(total);
+    let mut succeeded = 0;
+
+    while let Some(sample) = pending.next().await {
+        let Some(sample) = sample else {
+            continue;
+        };
+
+        if sample.success {
+            succeeded += 1;
+        }
+        samples.push(sample);
+
+        progress.on_progress(&BenchmarkProgress {
+            benchmark_id: benchmark_id.to_string(),
+            completed: samples.len(),
+            total,
+            succeeded,
+            failed: samples.len() - succeeded,
+            elapsed_ms: as_ms(start.elapsed()),
+        })?;
+    }
+
+    samples.sort_by_key(|s| s.index);
+
+    Ok(build_report(
+        benchmark_id,
+        config,
+        samples,
+        start.elapsed(),
+        cancel.is_cancelled(),
+    ))
+}
+
+/// Send one request; `None` if it was cancelled before completing
+#[allow(clippy::too_many_arguments)]
+async fn run_sample(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    request: &LLMRequest,
+    benchmark_id: &str,
+    index: usize,
+    benchmark_start: Instant,
+    queue_ms: Option<f64>,
+    cancel: &CancelToken,
+) -> Option<BenchmarkSample> {
+    let start = Instant::now();
+    let start_offset_ms = as_ms(start.duration_since(benchmark_start));
+
+    let result = if request.stream {
+        let request_id = format!("{}-{}", benchmark_id, index);
+        send_llm_request_streaming(http, endpoint, request, &request_id, cancel, &NullSink)
+            .await
+            .map(|r| (r.cancelled, r.usage, r.metrics))
+    } else {
+        send_llm_request(http, endpoint, request, cancel)
+            .await
+            .map(|r| (r.cancelled, r.usage, r.metrics))
+    };
+
+    match result {
+        Ok((true, _, _)) => None,
+        Ok((false, usage, metrics)) => Some(BenchmarkSample {
+            index,
+            start_offset_ms,
+            queue_ms,
+            success: true,
+            error: None,
+            usage,
+            metrics,
+        }),
+        Err(e) => Some(BenchmarkSample {
+            index,
+            start_offset_ms,
+            queue_ms,
+            success: false,
+            error: Some(e.to_string()),
+            usage: None,
+            metrics: failed_request_metrics(start.elapsed(), &e),
+        }),
+    }
+}
+
+/// Whether a successful sample met the configured TTFT/TPOT SLOs
+fn meets_slo(sample: &BenchmarkSample, config: &BenchmarkConfig) -> bool {
+    let ttft_ok = match config.slo_ttft_ms {
+        Some(slo) => sample.metrics.ttft_ms <= slo,
+        None => true,
+    };
+    // Single-token and non-streaming responses have no TPOT to violate
+    let tpot_ok = match (config.slo_tpot_ms, sample.metrics.avg_tpot_ms) {
+        (Some(slo), Some(tpot)) => tpot <= slo,
+        _ => true,
+    };
+    ttft_ok && tpot_ok
+}
+
+fn build_report(
+    benchmark_id: &str,
+    config: &BenchmarkConfig,
+    samples: Vec<BenchmarkSample>,
+    duration: Duration,
+    cancelled: bool,
+) -> BenchmarkReport {
+    let successes: Vec<&BenchmarkSample> = samples.iter().filter(|s| s.success).collect();
+    let completed = samples.len();
+    let succeeded = successes.len();
+    let failed = completed - succeeded;
+
+    let seconds = duration.as_secs_f64();
+    let per_second = |count: f64| if seconds > 0.0 { count / seconds } else { 0.0 };
+
+    let good = successes.iter().filter(|s| meets_slo(s, config)).count();
+    let output_tokens: usize = successes.iter().map(|s| s.metrics.total_tokens).sum();
+
+    let ttfts: Vec<f64> = successes.iter().map(|s| s.metrics.ttft_ms).collect();
+    let tpots: Vec<f64> = successes
+        .iter()
+        .filter_map(|s| s.metrics.avg_tpot_ms)
+        .collect();
+    let latencies: Vec<f64> = successes
+        .iter()
+        .map(|s| s.metrics.total_latency_ms)
+        .collect();
+    let queue: Vec<f64> = samples.iter().filter_map(|s| s.queue_ms).collect();
+
+    let retried: Vec<&RetryStats> = samples
+        .iter()
+        .filter_map(|s| s.metrics.retries.as_ref())
+        .filter(|r| r.attempts > 1)
+        .collect();
+
+    let mut error_counts: HashMap<&str, usize> = HashMap::new();
+    for error in samples.iter().filter_map(|s| s.error.as_deref()) {
+        *error_counts.entry(error).or_default() += 1;
+    }
+    let mut errors: Vec<ErrorCount> = error_counts
+        .into_iter()
+        .map(|(message, count)| ErrorCount {
+            message: message.to_string(),
+            count,
+        })
+        .collect();
+    errors.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.message.cmp(&b.message))
+    });
+
+    BenchmarkReport {
+        benchmark_id: benchmark_id.to_string(),
+        config: config.clone(),
+        completed,
+        succeeded,
+        failed,
+        cancelled,
+        duration_ms: as_ms(duration),
+        error_rate: if completed > 0 {
+            failed as f64 / completed as f64
+        } else {
+            0.0
+        },
+        requests_per_second: per_second(completed as f64),
+        goodput: per_second(good as f64),
+        output_tokens_per_second: per_second(output_tokens as f64),
+        ttft: latency_stats(&ttfts),
+        tpot: latency_stats(&tpots),
+        latency: latency_stats(&latencies),
+        queue: latency_stats(&queue),
+        retried: retried.len(),
+        retry_ms: retried
+            .iter()
+            .map(|r| r.failed_attempts_ms + r.wait_ms)
+            .sum(),
+        errors,
+        samples,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PerformanceMetrics;
+
+    fn config(slo_ttft_ms: Option<f64>, slo_tpot_ms: Option<f64>) -> BenchmarkConfig {
+        BenchmarkConfig {
+            total_requests: 10,
+            concurrency: 2,
+            target_qps: None,
+            slo_ttft_ms,
+            slo_tpot_ms,
+        }
+    }
+
+    fn success(index: usize, ttft_ms: f64, tpot_ms: Option<f64>, tokens: usize) -> BenchmarkSample {
+        BenchmarkSample {
+            index,
+            start_offset_ms: 0.0,
+            queue_ms: None,
+            success: true,
+            error: None,
+            usage: None,
+            metrics: PerformanceMetrics {
+                ttft_ms,
+                avg_tpot_ms: tpot_ms,
+                total_latency_ms: ttft_ms * 2.0,
+                total_tokens: tokens,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn failure(index: usize, error: &str) -> BenchmarkSample {
+        BenchmarkSample {
+            index,
+            start_offset_ms: 0.0,
+            queue_ms: None,
+            success: false,
+            error: Some(error.to_string()),
+            usage: None,
+            metrics: PerformanceMetrics {
+                total_latency_ms: 5.0,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_mixed_success_and_failure() {
+        let samples = vec![
+            success(0, 100.0, Some(10.0), 50),
+            failure(1, "HTTP 429"),
+            success(2, 300.0, Some(30.0), 70),
+            failure(3, "HTTP 500"),
+            failure(4, "HTTP 429"),
+        ];
+        let report = build_report(
+            "b",
+            &config(Some(200.0), None),
+            samples,
+            Duration::from_secs(2),
+            false,
+        );
+
+        assert_eq!(report.completed, 5);
+        assert_eq!(report.succeeded, 2);
+        assert_eq!(report.failed, 3);
+        assert_eq!(report.error_rate, 0.6);
+        assert_eq!(report.requests_per_second, 2.5);
+        // Only the first success meets the TTFT SLO
+        assert_eq!(report.goodput, 0.5);
+        assert_eq!(report.output_tokens_per_second, 60.0);
+        // Failed samples stay out of the latency distributions
+        let ttft = report.ttft.unwrap();
+        assert_eq!((ttft.min, ttft.max, ttft.mean), (100.0, 300.0, 200.0));
+        assert_eq!(report.latency.unwrap().max, 600.0);
+        // Most frequent error first
+        let errors: Vec<(&str, usize)> = report
+            .errors
+            .iter()
+            .map(|e| (e.message.as_str(), e.count))
+            .collect();
+        assert_eq!(errors, vec![("HTTP 429", 2), ("HTTP 500", 1)]);
+        assert_eq!(report.samples.len(), 5);
+    }
+
+    #[test]
+    fn test_no_successes() {
+        let samples = vec![failure(0, "refused"), failure(1, "refused")];
+        let report = build_report(
+            "b",
+            &config(Some(200.0), None),
+            samples,
+            Duration::from_secs(1),
+            false,
+        );
+
+        assert_eq!(report.error_rate, 1.0);
+        assert_eq!(report.goodput, 0.0);
+        assert_eq!(report.output_tokens_per_second, 0.0);
+        assert!(report.ttft.is_none() && report.tpot.is_none() && report.latency.is_none());
+    }
+
+    #[test]
+    fn test_cancelled_before_any_completed() {
+        let report = build_report("b", &config(None, None), Vec::new(), Duration::ZERO, true);
+        assert!(report.cancelled);
+        assert_eq!(report.completed, 0);
+        assert_eq!(report.error_rate, 0.0);
+        assert_eq!(report.requests_per_second, 0.0);
+        assert_eq!(report.goodput, 0.0);
+    }
+
+    #[test]
+    fn test_goodput_slos() {
+        let samples = vec![
+            success(0, 100.0, Some(10.0), 1),
+            // Fails the TPOT SLO
+            success(1, 100.0, Some(50.0), 1),
+            // No TPOT (non-streaming), so only TTFT counts
+            success(2, 100.0, None, 1),
+            // Fails the TTFT SLO
+            success(3, 900.0, Some(10.0), 1),
+        ];
+        let report = build_report(
+            "b",
+            &config(Some(500.0), Some(20.0)),
+            samples.clone(),
+            Duration::from_secs(1),
+            false,
+        );
+        assert_eq!(report.goodput, 2.0);
+
+        // Without SLOs every success counts
+        let report = build_report(
+            "b",
+            &config(None, None),
+            samples,
+            Duration::from_secs(1),
+            false,
+        );
+        assert_eq!(report.goodput, 4.0);
+        assert_eq!(report.tpot.unwrap().max, 50.0);
+    }
+
+    #[test]
+    fn test_percentiles() {
+        // TTFTs 10, 20, ..., 100 in reverse order
+        let samples: Vec<BenchmarkSample> = (1..=10)
+            .rev()
+            .map(|v| success(v, v as f64 * 10.0, None, 1))
+            .collect();
+        let report = build_report(
+            "b",
+            &config(None, None),
+            samples,
+            Duration::from_secs(1),
+            false,
+        );
+
+        let ttft = report.ttft.unwrap();
+        assert!((ttft.p50 - 55.0).abs() < 1e-9);
+        assert!((ttft.p90 - 91.0).abs() < 1e-9);
+        assert!((ttft.p99 - 99.1).abs() < 1e-9);
+        assert!(report.tpot.is_none());
+    }
+
+    #[test]
+    fn test_queue_delay() {
+        let mut queued = success(0, 100.0, None, 1);
+        queued.queue_ms = Some(250.0);
+        let mut failed = failure(1, "HTTP 500");
+        failed.queue_ms = Some(50.0);
+        let report = build_report(
+            "b",
+            &config(None, None),
+            vec![queued, failed],
+            Duration::from_secs(1),
+            false,
+        );
+        // Failed requests waited for a slot too
+        let queue = report.queue.unwrap();
+        assert_eq!((queue.min, queue.max), (50.0, 250.0));
+
+        let report = build_report(
+            "b",
+            &config(None, None),
+            vec![success(0, 100.0, None, 1)],
+            Duration::from_secs(1),
+            false,
+        );
+        assert!(report.queue.is_none());
+    }
+
+    #[test]
+    fn test_retry_totals() {
+        let mut retried = success(0, 100.0, None, 1);
+        retried.metrics.retries = Some(RetryStats {
+            attempts: 3,
+            failed_attempts_ms: 40.0,
+            wait_ms: 1500.0,
+            errors: vec!["HTTP 503".to_string(); 2],
+        });
+        let mut first_try = success(1, 100.0, None, 1);
+        first_try.metrics.retries = Some(RetryStats {
+            attempts: 1,
+            failed_attempts_ms: 0.0,
+            wait_ms: 0.0,
+            errors: Vec::new(),
+        });
+        let report = build_report(
+            "b",
+            &config(None, None),
+            vec![retried, first_try],
+            Duration::from_secs(1),
+            false,
+        );
+
+        assert_eq!(report.retried, 1);
+        assert_eq!(report.retry_ms, 1540.0);
+    }
+
+    #[test]
+    fn test_qps_schedule() {
+        let offsets: Vec<Duration> = (0..5).map(|index| scheduled_offset(index, 4.0)).collect();
+        assert_eq!(
+            offsets,
+            [0, 250, 500, 750, 1000].map(Duration::from_millis).to_vec()
+        );
+        // Fractional rates spread requests further apart than a second
+        assert_eq!(scheduled_offset(3, 0.5), Duration::from_secs(6));
+    }
+
+    #[test]
+    fn test_invalid_configs() {
+        let mut invalid = config(None, None);
+        invalid.total_requests = 0;
+        assert!(validate_config(&invalid).is_err());
+
+        let mut invalid = config(None, None);
+        invalid.concurrency = 0;
+        assert!(validate_config(&invalid).is_err());
+
+        for qps in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let mut invalid = config(None, None);
+            invalid.target_qps = Some(qps);
+            assert!(validate_config(&invalid).is_err(), "{qps}");
+        }
+        let mut valid = config(None, None);
+        valid.target_qps = Some(0.5);
+        assert!(validate_config(&valid).is_ok());
+    }
+}
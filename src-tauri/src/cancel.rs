@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+/// Cooperative cancellation signal shared between a request and `cancel_request`
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Resolves once `cancel` has been called
+    pub async fn cancelled(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives as long as `self`, so this cannot fail
+        let _ = receiver.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// In-flight requests keyed by request ID, kept in Tauri state
+#[derive(Default)]
+pub struct RequestRegistry {
+    tokens: Mutex<HashMap<String, CancelToken>>,
+}
+
+impl RequestRegistry {
+    pub fn register(&self, request_id: &str) -> CancelToken {
+        let token = CancelToken::new();
+        self.tokens
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(request_id.to_string(), token.clone());
+        token
+    }
+
+    pub fn unregister(&self, request_id: &str) {
+        self.tokens
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(request_id);
+    }
+
+    /// Cancel a request; returns false if no such request is in flight
+    pub fn cancel(&self, request_id: &str) -> bool {
+        match self
+            .tokens
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(request_id)
+        {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_register_and_cancel() {
+        let registry = RequestRegistry::default();
+        let token = registry.register("a");
+        let other = registry.register("b");
+
+        assert!(!token.is_cancelled());
+        assert!(registry.cancel("a"));
+        assert!(token.is_cancelled());
+        // Only the named request is cancelled
+        assert!(!other.is_cancelled());
+    }
+
+    #[test]
+    fn test_cancel_unknown_request() {
+        let registry = RequestRegistry::default();
+        assert!(!registry.cancel("missing"));
+    }
+
+    #[test]
+    fn test_unregister_after_completion() {
+        let registry = RequestRegistry::default();
+        let token = registry.register("a");
+        registry.unregister("a");
+
+        assert!(!registry.cancel("a"));
+        assert!(!token.is_cancelled());
+        // Unregistering twice is harmless
+        registry.unregister("a");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_wakes_select() {
+        let registry = Arc::new(RequestRegistry::default());
+        let token = registry.register("a");
+
+        let waiting = tokio::spawn(async move {
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(60)) => false,
+                _ = token.cancelled() => true,
+            }
+        });
+        // Let the task start waiting before cancelling
+        tokio::task::yield_now().await;
+        assert!(registry.cancel("a"));
+
+        let cancelled = tokio::time::timeout(Duration::from_secs(5), waiting)
+            .await
+            .expect("the cancelled arm did not finish")
+            .unwrap();
+        assert!(cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_resolves_after_cancel() {
+        let token = CancelToken::new();
+        token.cancel();
+        // Waiting on an already cancelled token returns at once
+        tokio::time::timeout(Duration::from_secs(5), token.cancelled())
+            .await
+            .unwrap();
+    }
+}
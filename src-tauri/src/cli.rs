@@ -0,0 +1,1032 @@
+use crate::batch::{default_output_path, run_batch, BatchSink};
+use crate::bench::{run_benchmark, ProgressSink};
+use crate::cancel::CancelToken;
+use crate::client::HttpClient;
+use crate::commands::{execute_request, execute_streaming_request, get_saved_endpoints};
+use crate::http::{NullSink, StreamSink};
+use crate::media::{audio_part, image_part};
+use crate::types::{
+    ApiStyle, AppError, BatchConfig, BatchProgress, BatchSummary, BenchmarkConfig,
+    BenchmarkProgress, BenchmarkReport, ContentPart, Endpoint, JsonSchemaFormat, LLMRequest,
+    LatencyStats, LogprobStats, Message, MessageContent, OutputValidation, PerformanceMetrics,
+    ProviderResolution, ProviderSetting, ReasoningConfig, ResponseFormat, RetryPolicy,
+    TimeoutConfig, Tool, ToolCall, UsageMetrics,
+};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use std::ffi::OsStr;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+/// Headless "curl for models": send prompts and print performance metrics
+#[derive(Parser)]
+#[command(name = "modelcurl", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Send a prompt to an endpoint and print the response and metrics
+    Send(Box<SendArgs>),
+    /// Load-test an endpoint and report latency percentiles, goodput and errors
+    Bench(Box<BenchArgs>),
+    /// Replay a JSONL file of requests and write a results JSONL
+    Batch(Box<BatchArgs>),
+    /// List saved endpoints
+    Endpoints,
+}
+
+#[derive(Args)]
+pub(crate) struct TargetArgs {
+    /// Saved endpoint name or ID (from endpoints.json)
+    #[arg(short, long, conflicts_with = "url")]
+    pub endpoint: Option<String>,
+
+    /// Base URL for an ad-hoc endpoint (e.g. http://localhost:8000/v1)
+    #[arg(long)]
+    pub url: Option<String>,
+
+    /// API key for an ad-hoc endpoint
+    #[arg(long, env = "MODELCURL_API_KEY")]
+    pub api_key: Option<String>,
+
+    /// Wire protocol, e.g. chat_completions or anthropic_messages
+    #[arg(long, value_parser = parse_api_style)]
+    pub api_style: Option<ApiStyle>,
+
+    /// Provider family: auto, generic, openai, deepseek, qwen, claude or gemini
+    #[arg(long, value_parser = parse_provider)]
+    pub provider: Option<ProviderSetting>,
+
+    /// Model name (overrides the saved endpoint's model)
+    #[arg(short, long)]
+    pub model: Option<String>,
+
+    /// Retry 429s, 5xx, connection failures and timeouts up to N times, with backoff
+    #[arg(long, value_name = "N")]
+    pub retries: Option<u32>,
+}
+
+#[derive(Args)]
+pub(crate) struct GenerationArgs {
+    /// System message
+    #[arg(short, long)]
+    pub system: Option<String>,
+
+    #[arg(short, long, default_value_t = 0.0)]
+    pub temperature: f32,
+
+    #[arg(long, default_value_t = 2048)]
+    pub max_tokens: u32,
+
+    /// Enable thinking for hybrid reasoning models
+    #[arg(long)]
+    pub thinking: bool,
+
+    /// Reasoning effort for OpenAI reasoning models
+    #[arg(long)]
+    pub reasoning_effort: Option<String>,
+
+    /// Thinking budget tokens for Claude/Qwen
+    #[arg(long)]
+    pub thinking_budget: Option<u32>,
+
+    /// JSON file with an OpenAI-style `tools` array
+    #[arg(long)]
+    pub tools: Option<PathBuf>,
+
+    /// `auto`, `none`, `required`, or the name of a tool to force
+    #[arg(long)]
+    pub tool_choice: Option<String>,
+
+    /// Attach an image (local file or URL); repeatable
+    #[arg(long = "image", value_name = "PATH_OR_URL")]
+    pub images: Vec<String>,
+
+    /// Attach a wav or mp3 file; repeatable
+    #[arg(long = "audio", value_name = "PATH")]
+    pub audio: Vec<PathBuf>,
+
+    /// Request JSON output (`response_format: json_object`)
+    #[arg(long, conflicts_with = "json_schema")]
+    pub json: bool,
+
+    /// Request output matching the JSON Schema in this file and validate it
+    #[arg(long, value_name = "FILE")]
+    pub json_schema: Option<PathBuf>,
+
+    /// Echo the prompt before the completion (`--api-style completions`)
+    #[arg(long)]
+    pub echo: bool,
+
+    /// Text after the completion, for fill-in-the-middle (`--api-style completions`)
+    #[arg(long)]
+    pub suffix: Option<String>,
+
+    /// Return token logprobs and report mean logprob, perplexity and the least
+    /// likely tokens
+    #[arg(long)]
+    pub logprobs: bool,
+
+    /// Also return the N most likely alternatives at each position
+    #[arg(long, value_name = "N")]
+    pub top_logprobs: Option<u32>,
+}
+
+#[derive(Args)]
+struct SendArgs {
+    #[command(flatten)]
+    target: TargetArgs,
+
+    #[command(flatten)]
+    generation: GenerationArgs,
+
+    /// Disable streaming (metrics then only cover total latency)
+    #[arg(long)]
+    no_stream: bool,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+
+    /// Prompt text; read from stdin when omitted or "-"
+    prompt: Option<String>,
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    #[command(flatten)]
+    target: TargetArgs,
+
+    #[command(flatten)]
+    generation: GenerationArgs,
+
+    /// Total number of requests to send
+    #[arg(short = 'n', long, default_value_t = 10)]
+    requests: usize,
+
+    /// Maximum number of requests in flight
+    #[arg(short, long, default_value_t = 1)]
+    concurrency: usize,
+
+    /// Release requests at a fixed rate (requests per second), even while earlier ones
+    /// are still running
+    #[arg(long)]
+    qps: Option<f64>,
+
+    /// TTFT SLO in milliseconds for goodput
+    #[arg(long)]
+    slo_ttft_ms: Option<f64>,
+
+    /// TPOT SLO in milliseconds for goodput
+    #[arg(long)]
+    slo_tpot_ms: Option<f64>,
+
+    /// Disable streaming (TTFT then equals total latency)
+    #[arg(long)]
+    no_stream: bool,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+
+    /// Prompt text; read from stdin when omitted or "-"
+    prompt: Option<String>,
+}
+
+#[derive(Args)]
+struct BatchArgs {
+    #[command(flatten)]
+    target: TargetArgs,
+
+    /// JSONL file of LLMRequest objects or raw OpenAI request bodies
+    input: PathBuf,
+
+    /// Results file (defaults to <input>.results.jsonl)
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    /// Maximum number of lines in flight
+    #[arg(short, long, default_value_t = 1)]
+    concurrency: usize,
+
+    /// Stream every line, regardless of its own setting
+    #[arg(long, conflicts_with = "no_stream")]
+    stream: bool,
+
+    /// Disable streaming for every line
+    #[arg(long)]
+    no_stream: bool,
+
+    /// Summary format
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+}
+
+/// Parse an API style by its serialized name (e.g. `anthropic_messages`)
+fn parse_api_style(value: &str) -> Result<ApiStyle, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| format!("Unknown API style '{}'", value))
+}
+
+/// Parse a provider setting by its serialized name (e.g. `deepseek`)
+fn parse_provider(value: &str) -> Result<ProviderSetting, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_lowercase()))
+        .map_err(|_| format!("Unknown provider '{}'", value))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// Stream tokens to stdout, then print a metrics table
+    Table,
+    /// Print a single JSON document with the response and metrics
+    Json,
+}
+
+/// Writes streamed tokens to stdout and reasoning to stderr
+struct StdoutSink;
+
+impl StreamSink for StdoutSink {
+    fn on_token(&self, token: &str) -> Result<(), String> {
+        let mut stdout = io::stdout().lock();
+        write!(stdout, "{}", token)
+            .and_then(|_| stdout.flush())
+            .map_err(|e| format!("Failed to write token: {}", e))
+    }
+
+    fn on_reasoning_token(&self, token: &str) -> Result<(), String> {
+        let mut stderr = io::stderr().lock();
+        write!(stderr, "{}", token)
+            .and_then(|_| stderr.flush())
+            .map_err(|e| format!("Failed to write reasoning token: {}", e))
+    }
+}
+
+/// Redraws a single progress line on stderr
+struct StderrProgress;
+
+impl ProgressSink for StderrProgress {
+    fn on_progress(&self, progress: &BenchmarkProgress) -> Result<(), String> {
+        let mut stderr = io::stderr().lock();
+        write!(
+            stderr,
+            "\r{}/{} done, {} ok, {} failed, {:.1} s",
+            progress.completed,
+            progress.total,
+            progress.succeeded,
+            progress.failed,
+            progress.elapsed_ms / 1000.0
+        )
+        .and_then(|_| stderr.flush())
+        .map_err(|e| format!("Failed to write progress: {}", e))
+    }
+}
+
+/// Discards benchmark progress (JSON mode)
+struct NullProgress;
+
+impl ProgressSink for NullProgress {
+    fn on_progress(&self, _progress: &BenchmarkProgress) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Prints one status line per batch result on stderr
+struct StderrBatchProgress;
+
+impl BatchSink for StderrBatchProgress {
+    fn on_result(&self, progress: &BatchProgress) -> Result<(), String> {
+        let result = &progress.result;
+        let status = match &result.error {
+            Some(error) => format!("error: {}", error),
+            None => format!("ok, {}", format_ms(Some(result.metrics.total_latency_ms))),
+        };
+        eprintln!(
+            "[{}/{}] line {}: {}",
+            progress.completed, progress.total, result.line, status
+        );
+        Ok(())
+    }
+}
+
+/// Discards batch progress (JSON mode)
+struct NullBatchProgress;
+
+impl BatchSink for NullBatchProgress {
+    fn on_result(&self, _progress: &BatchProgress) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Whether the first argument asks for the CLI: a known subcommand, `help`, or the
+/// help and version flags. Anything else (e.g. macOS's `-psn_*` from Finder) opens the GUI.
+fn is_cli_invocation(first: Option<&OsStr>) -> bool {
+    let Some(first) = first.and_then(OsStr::to_str) else {
+        return false;
+    };
+    matches!(first, "help" | "-h" | "--help" | "-V" | "--version")
+        || Cli::command().find_subcommand(first).is_some()
+}
+
+/// Release builds on Windows use the GUI subsystem and start without a console, so
+/// output would go nowhere; write to the console of the shell that started the CLI
+#[cfg(windows)]
+fn attach_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    // Fails when there is no parent console (e.g. started from Explorer), which is fine
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+/// Run the CLI if invoked with a subcommand; returns `None` to launch the GUI instead
+pub fn run_from_args() -> Option<i32> {
+    if !is_cli_invocation(std::env::args_os().nth(1).as_deref()) {
+        return None;
+    }
+    attach_console();
+
+    let cli = Cli::parse();
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start async runtime: {}", e);
+            return Some(1);
+        }
+    };
+
+    let result = runtime.block_on(async {
+        match cli.command {
+            Command::Send(args) => send(*args).await,
+            Command::Bench(args) => bench(*args).await,
+            Command::Batch(args) => batch(*args).await,
+            Command::Endpoints => list_endpoints(),
+        }
+    });
+
+    Some(match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    })
+}
+
+/// Resolve a saved endpoint by name/ID, or build an ad-hoc one from `--url`.
+/// The model may be left empty; commands that need one call `require_model`.
+pub(crate) fn resolve_endpoint(target: &TargetArgs) -> Result<Endpoint, String> {
+    let mut endpoint = if let Some(url) = &target.url {
+        Endpoint {
+            id: "cli".to_string(),
+            name: url.clone(),
+            url: url.trim_end_matches('/').to_string(),
+            api_key: None,
+            headers: Vec::new(),
+            model: String::new(),
+            api_style: ApiStyle::default(),
+            provider: ProviderSetting::default(),
+            timeouts: TimeoutConfig::default(),
+            retry: RetryPolicy::default(),
+        }
+    } else {
+        let endpoints = get_saved_endpoints()?;
+        match &target.endpoint {
+            Some(key) => endpoints
+                .into_iter()
+                .find(|e| e.id == *key || e.name.eq_ignore_ascii_case(key))
+                .ok_or_else(|| format!("No saved endpoint named '{}'", key))?,
+            None => {
+                let mut endpoints = endpoints;
+                match endpoints.len() {
+                    0 => return Err("No saved endpoints; pass --url".to_string()),
+                    1 => endpoints.remove(0),
+                    _ => {
+                        return Err(
+                            "Several endpoints are saved; pick one with --endpoint".to_string()
+                        )
+                    }
+                }
+            }
+        }
+    };
+
+    if let Some(api_key) = &target.api_key {
+        endpoint.api_key = Some(api_key.clone());
+    }
+    if let Some(api_style) = target.api_style {
+        endpoint.api_style = api_style;
+    }
+    if let Some(provider) = target.provider {
+        endpoint.provider = provider;
+    }
+    if let Some(model) = &target.model {
+        endpoint.model = model.clone();
+    }
+    if let Some(retries) = target.retries {
+        endpoint.retry.max_attempts = retries.saturating_add(1);
+    }
+
+    Ok(endpoint)
+}
+
+fn require_model(endpoint: &Endpoint) -> Result<(), String> {
+    if endpoint.model.is_empty() {
+        return Err("No model configured; pass --model".to_string());
+    }
+    Ok(())
+}
+
+/// Build an `LLMRequest` from CLI generation flags
+pub(crate) fn build_request(
+    endpoint: &Endpoint,
+    generation: &GenerationArgs,
+    prompt: String,
+    stream: bool,
+) -> Result<LLMRequest, String> {
+    let mut messages = Vec::new();
+    if let Some(system) = &generation.system {
+        messages.push(Message {
+            role: "system".to_string(),
+            content: system.clone().into(),
+            ..Default::default()
+        });
+    }
+
+    let content = if generation.images.is_empty() && generation.audio.is_empty() {
+        MessageContent::Text(prompt)
+    } else {
+        let mut parts = vec![ContentPart::Text { text: prompt }];
+        for image in &generation.images {
+            parts.push(image_part(image)?);
+        }
+        for audio in &generation.audio {
+            parts.push(audio_part(audio)?);
+        }
+        MessageContent::Parts(parts)
+    };
+    messages.push(Message {
+        role: "user".to_string(),
+        content,
+        ..Default::default()
+    });
+
+    let tools = match &generation.tools {
+        Some(path) => read_tools(path)?,
+        None => Vec::new(),
+    };
+    let tool_choice = generation
+        .tool_choice
+        .as_deref()
+        .map(|choice| match choice {
+            "auto" | "none" | "required" => serde_json::json!(choice),
+            name => serde_json::json!({ "type": "function", "function": { "name": name } }),
+        });
+
+    let wants_reasoning = generation.thinking
+        || generation.reasoning_effort.is_some()
+        || generation.thinking_budget.is_some();
+
+    let response_format = match &generation.json_schema {
+        Some(path) => Some(read_schema(path)?),
+        None => generation.json.then_some(ResponseFormat::JsonObject),
+    };
+
+    Ok(LLMRequest {
+        model: endpoint.model.clone(),
+        messages,
+        temperature: generation.temperature,
+        max_tokens: generation.max_tokens,
+        stream,
+        tools,
+        tool_choice,
+        response_format,
+        prompt: None,
+        echo: generation.echo,
+        suffix: generation.suffix.clone(),
+        logprobs: generation.logprobs || generation.top_logprobs.is_some(),
+        top_logprobs: generation.top_logprobs,
+        reasoning_config: wants_reasoning.then(|| ReasoningConfig {
+            enable_thinking: generation.thinking,
+            reasoning_effort: generation.reasoning_effort.clone(),
+            max_completion_tokens: None,
+            thinking_budget_tokens: generation.thinking_budget,
+        }),
+    })
+}
+
+/// `response_format` for a schema file, named after the file
+fn read_schema(path: &PathBuf) -> Result<ResponseFormat, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let schema =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid schema file: {}", e))?;
+
+    Ok(ResponseFormat::JsonSchema {
+        json_schema: JsonSchemaFormat {
+            name: path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("schema")
+                .to_string(),
+            description: None,
+            schema,
+            strict: Some(true),
+        },
+    })
+}
+
+fn read_tools(path: &PathBuf) -> Result<Vec<Tool>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid tools file: {}", e))
+}
+
+fn read_prompt(prompt: Option<String>) -> Result<String, String> {
+    match prompt {
+        Some(prompt) if prompt != "-" => Ok(prompt),
+        _ => {
+            let mut buffer = String::new();
+            io::stdin()
+                .read_to_string(&mut buffer)
+                .map_err(|e| format!("Failed to read prompt from stdin: {}", e))?;
+            Ok(buffer)
+        }
+    }
+}
+
+/// Cancel the request on Ctrl-C so partial output and metrics are still printed
+fn cancel_on_ctrl_c() -> CancelToken {
+    let cancel = CancelToken::new();
+    let token = cancel.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            token.cancel();
+        }
+    });
+    cancel
+}
+
+async fn send(args: SendArgs) -> Result<(), String> {
+    let endpoint = resolve_endpoint(&args.target)?;
+    require_model(&endpoint)?;
+    let prompt = read_prompt(args.prompt)?;
+    let stream = !args.no_stream;
+    let request = build_request(&endpoint, &args.generation, prompt, stream)?;
+
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let cancel = cancel_on_ctrl_c();
+
+    if stream {
+        let sink: &dyn StreamSink = match args.output {
+            OutputFormat::Table => &StdoutSink,
+            OutputFormat::Json => &NullSink,
+        };
+
+        let streamed = execute_streaming_request(
+            &HttpClient::default(),
+            &endpoint,
+            &request,
+            &request_id,
+            &cancel,
+            sink,
+        )
+        .await
+        .map_err(print_error_warnings)?;
+
+        match args.output {
+            OutputFormat::Table => {
+                println!();
+                if streamed.cancelled {
+                    eprintln!("(cancelled)");
+                }
+                print_tool_calls(&streamed.tool_calls);
+                print_validation(streamed.validation.as_ref());
+                print_logprob_stats(streamed.logprob_stats.as_ref());
+                print_provider(streamed.provider_resolution.as_ref());
+                print_warnings(&streamed.warnings);
+                print_metrics_table(&streamed.metrics, streamed.usage.as_ref());
+            }
+            OutputFormat::Json => print_json(&streamed)?,
+        }
+    } else {
+        let response = execute_request(
+            &HttpClient::default(),
+            &endpoint,
+            &request,
+            &request_id,
+            &cancel,
+        )
+        .await
+        .map_err(print_error_warnings)?;
+
+        match args.output {
+            OutputFormat::Table => {
+                if let Some(reasoning) = &response.reasoning_content {
+                    eprintln!("{}", reasoning);
+                }
+                println!("{}", response.content);
+                print_tool_calls(&response.tool_calls);
+                print_validation(response.validation.as_ref());
+                print_logprob_stats(response.logprob_stats.as_ref());
+                print_provider(response.provider_resolution.as_ref());
+                print_warnings(&response.warnings);
+                println!();
+                print_metrics_table(&response.metrics, response.usage.as_ref());
+            }
+            OutputFormat::Json => print_json(&response)?,
+        }
+    }
+
+    Ok(())
+}
+
+async fn bench(args: BenchArgs) -> Result<(), String> {
+    let endpoint = resolve_endpoint(&args.target)?;
+    require_model(&endpoint)?;
+    let prompt = read_prompt(args.prompt)?;
+    let request = build_request(&endpoint, &args.generation, prompt, !args.no_stream)?;
+
+    let config = BenchmarkConfig {
+        total_requests: args.requests,
+        concurrency: args.concurrency,
+        target_qps: args.qps,
+        slo_ttft_ms: args.slo_ttft_ms,
+        slo_tpot_ms: args.slo_tpot_ms,
+    };
+
+    let benchmark_id = uuid::Uuid::new_v4().to_string();
+    let cancel = cancel_on_ctrl_c();
+    let progress: &dyn ProgressSink = match args.output {
+        OutputFormat::Table => &StderrProgress,
+        OutputFormat::Json => &NullProgress,
+    };
+
+    let report = run_benchmark(
+        &HttpClient::default(),
+        &endpoint,
+        &request,
+        &config,
+        &benchmark_id,
+        &cancel,
+        progress,
+    )
+    .await?;
+
+    match args.output {
+        OutputFormat::Table => {
+            eprintln!();
+            print_benchmark_report(&report);
+        }
+        OutputFormat::Json => print_json(&report)?,
+    }
+
+    Ok(())
+}
+
+async fn batch(args: BatchArgs) -> Result<(), String> {
+    let endpoint = resolve_endpoint(&args.target)?;
+    let output = args.out.unwrap_or_else(|| default_output_path(&args.input));
+
+    let config = BatchConfig {
+        concurrency: args.concurrency,
+        stream: if args.stream {
+            Some(true)
+        } else if args.no_stream {
+            Some(false)
+        } else {
+            None
+        },
+    };
+
+    let batch_id = uuid::Uuid::new_v4().to_string();
+    let cancel = cancel_on_ctrl_c();
+    let sink: &dyn BatchSink = match args.output {
+        OutputFormat::Table => &StderrBatchProgress,
+        OutputFormat::Json => &NullBatchProgress,
+    };
+
+    let summary = run_batch(
+        &HttpClient::default(),
+        &endpoint,
+        &args.input,
+        &output,
+        &config,
+        &batch_id,
+        &cancel,
+        sink,
+    )
+    .await?;
+
+    match args.output {
+        OutputFormat::Table => print_batch_summary(&summary),
+        OutputFormat::Json => print_json(&summary)?,
+    }
+
+    Ok(())
+}
+
+fn list_endpoints() -> Result<(), String> {
+    let endpoints = get_saved_endpoints()?;
+
+    if endpoints.is_empty() {
+        println!("No saved endpoints");
+        return Ok(());
+    }
+
+    for endpoint in endpoints {
+        println!(
+            "{:<24} {:<32} {:<48} {}",
+            endpoint.name, endpoint.model, endpoint.url, endpoint.id
+        );
+    }
+
+    Ok(())
+}
+
+pub(crate) fn print_json<T: serde::Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize output: {}", e))?;
+    println!("{}", json);
+    Ok(())
+}
+
+fn format_ms(value: Option<f64>) -> String {
+    value
+        .map(|ms| format!("{:.1} ms", ms))
+        .unwrap_or_else(|| "N/A".to_string())
+}
+
+/// Report problems that did not fail the request, such as history not being saved
+fn print_warnings(warnings: &[String]) {
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+}
+
+/// Report the warnings of a failed request; its error is printed on the way out
+fn print_error_warnings(error: AppError) -> AppError {
+    print_warnings(error.warnings.as_deref().map_or(&[], Vec::as_slice));
+    error
+}
+
+/// Report JSON/schema validation on stderr so stdout stays the raw output
+fn print_validation(validation: Option<&OutputValidation>) {
+    let Some(validation) = validation else {
+        return;
+    };
+
+    if let Some(error) = &validation.parse_error {
+        eprintln!("Output is not valid JSON: {}", error);
+    } else if validation.valid {
+        eprintln!(
+            "Output matches the requested format ({:.2} ms to validate)",
+            validation.validation_ms
+        );
+    } else {
+        eprintln!("Output does not match the requested format:");
+        for error in &validation.errors {
+            let path = if error.path.is_empty() {
+                "/"
+            } else {
+                &error.path
+            };
+            eprintln!("  {}: {}", path, error.message);
+        }
+    }
+}
+
+/// Summarize token logprobs on stderr, with the least likely tokens
+fn print_logprob_stats(stats: Option<&LogprobStats>) {
+    let Some(stats) = stats else {
+        return;
+    };
+
+    eprintln!(
+        "Logprobs: {} tokens, mean {:.4}, perplexity {:.3}",
+        stats.token_count, stats.mean_logprob, stats.perplexity
+    );
+    for token in &stats.lowest_tokens {
+        eprintln!(
+            "  #{:<5} {:>9.4}  {:?}",
+            token.index, token.logprob, token.token
+        );
+    }
+}
+
+/// Report which adapter handled the request and why, on stderr
+fn print_provider(resolution: Option<&ProviderResolution>) {
+    if let Some(resolution) = resolution {
+        eprintln!("Adapter: {} ({})", resolution.adapter, resolution.reason);
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+fn print_tool_calls(tool_calls: &[ToolCall]) {
+    for call in tool_calls {
+        println!(
+            "-> {}({}) [{}]",
+            call.function.name, call.function.arguments, call.id
+        );
+    }
+}
+
+pub(crate) fn print_metrics_table(metrics: &PerformanceMetrics, usage: Option<&UsageMetrics>) {
+    let mut rows = vec![
+        ("TTFT", format_ms(Some(metrics.ttft_ms))),
+        ("TPOT", format_ms(metrics.avg_tpot_ms)),
+        ("Total latency", format_ms(Some(metrics.total_latency_ms))),
+        ("Output tokens", metrics.total_tokens.to_string()),
+        (
+            "Tokens/s",
+            metrics
+                .tokens_per_second
+                .map(|t| format!("{:.1}", t))
+                .unwrap_or_else(|| "N/A".to_string()),
+        ),
+    ];
+
+    if metrics.time_to_first_reasoning_ms.is_some() {
+        rows.push((
+            "First reasoning",
+            format_ms(metrics.time_to_first_reasoning_ms),
+        ));
+        rows.push(("First answer", format_ms(metrics.time_to_first_answer_ms)));
+    }
+
+    if metrics.request_bytes > 0 {
+        rows.push(("Request size", format_bytes(metrics.request_bytes)));
+    }
+
+    if let Some(reused) = metrics.connection_reused {
+        let connection = if reused { "reused" } else { "new" };
+        rows.push(("Connection", connection.to_string()));
+    }
+
+    // The timings above cover the last attempt only
+    if let Some(retries) = metrics.retries.as_ref().filter(|r| r.attempts > 1) {
+        rows.push(("Attempts", retries.attempts.to_string()));
+        rows.push((
+            "Retry time",
+            format_ms(Some(retries.failed_attempts_ms + retries.wait_ms)),
+        ));
+    }
+
+    // Cumulative, like curl's -w timings; phases that did not happen are skipped
+    if let Some(network) = &metrics.network {
+        let phases = [
+            ("DNS lookup", network.dns_ms),
+            ("Connected", network.connect_ms),
+            ("Request sent", network.upload_ms),
+            ("Headers", network.headers_ms),
+            ("First byte", network.first_byte_ms),
+            ("First token", network.first_token_ms),
+            ("Transfer end", network.transfer_end_ms),
+        ];
+        for (label, value) in phases {
+            if value.is_some() {
+                rows.push((label, format!("@ {}", format_ms(value))));
+            }
+        }
+    }
+
+    if let Some(first) = metrics.tool_calls.first() {
+        rows.push(("Tool calls", metrics.tool_calls.len().to_string()));
+        rows.push(("First call done", format_ms(first.completed_ms)));
+    }
+
+    if let Some(usage) = usage {
+        rows.push(("Prompt tokens", usage.prompt_tokens.to_string()));
+        rows.push(("Completion tokens", usage.completion_tokens.to_string()));
+        if let Some(reasoning) = usage.reasoning_tokens {
+            rows.push(("Reasoning tokens", reasoning.to_string()));
+        }
+        if let Some(cached) = usage.cache_read_tokens {
+            rows.push(("Cached tokens", cached.to_string()));
+        }
+    }
+
+    for (label, value) in rows {
+        println!("{:<18} {:>14}", label, value);
+    }
+}
+
+fn print_batch_summary(summary: &BatchSummary) {
+    if summary.cancelled {
+        println!("(cancelled)");
+    }
+
+    let rows = [
+        ("Lines", format!("{}/{}", summary.completed, summary.total)),
+        ("Succeeded", summary.succeeded.to_string()),
+        ("Failed", summary.failed.to_string()),
+        ("Invalid outputs", summary.invalid_outputs.to_string()),
+        ("Duration", format!("{:.2} s", summary.duration_ms / 1000.0)),
+        ("TTFT p50", format_ms(summary.ttft.as_ref().map(|s| s.p50))),
+        (
+            "Latency p50",
+            format_ms(summary.latency.as_ref().map(|s| s.p50)),
+        ),
+        ("Results", summary.output_path.clone()),
+    ];
+    for (label, value) in rows {
+        println!("{:<18} {:>14}", label, value);
+    }
+}
+
+fn print_benchmark_report(report: &BenchmarkReport) {
+    if report.cancelled {
+        println!("(cancelled)");
+    }
+
+    let rows = [
+        (
+            "Requests",
+            format!("{}/{}", report.completed, report.config.total_requests),
+        ),
+        ("Succeeded", report.succeeded.to_string()),
+        ("Failed", report.failed.to_string()),
+        ("Error rate", format!("{:.1}%", report.error_rate * 100.0)),
+        ("Retried", report.retried.to_string()),
+        ("Retry time", format!("{:.2} s", report.retry_ms / 1000.0)),
+        ("Duration", format!("{:.2} s", report.duration_ms / 1000.0)),
+        ("Requests/s", format!("{:.2}", report.requests_per_second)),
+        ("Goodput", format!("{:.2} req/s", report.goodput)),
+        (
+            "Output tokens/s",
+            format!("{:.1}", report.output_tokens_per_second),
+        ),
+    ];
+    for (label, value) in rows {
+        println!("{:<18} {:>14}", label, value);
+    }
+
+    println!();
+    println!(
+        "{:<10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        "(ms)", "min", "mean", "p50", "p90", "p99", "max"
+    );
+    let mut distributions: Vec<(&str, &Option<LatencyStats>)> = vec![
+        ("TTFT", &report.ttft),
+        ("TPOT", &report.tpot),
+        ("Latency", &report.latency),
+    ];
+    if report.config.target_qps.is_some() {
+        distributions.push(("Queue", &report.queue));
+    }
+    for (label, stats) in distributions {
+        match stats {
+            Some(s) => println!(
+                "{:<10} {:>10.1} {:>10.1} {:>10.1} {:>10.1} {:>10.1} {:>10.1}",
+                label, s.min, s.mean, s.p50, s.p90, s.p99, s.max
+            ),
+            None => println!("{:<10} {:>10}", label, "N/A"),
+        }
+    }
+
+    if !report.errors.is_empty() {
+        println!();
+        println!("Errors:");
+        for error in &report.errors {
+            println!("{:>6}x {}", error.count, error.message);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cli(first: &str) -> bool {
+        is_cli_invocation(Some(OsStr::new(first)))
+    }
+
+    #[test]
+    fn test_subcommands_and_help_run_the_cli() {
+        for first in ["send", "bench", "batch", "endpoints", "help", "--help"] {
+            assert!(cli(first), "{first}");
+        }
+        assert!(cli("-V"));
+    }
+
+    #[test]
+    fn test_other_arguments_open_the_gui() {
+        assert!(!is_cli_invocation(None));
+        assert!(!cli("-psn_0_12345"));
+        assert!(!cli("--unknown"));
+        assert!(!cli("sned"));
+    }
+}
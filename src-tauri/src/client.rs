@@ -0,0 +1,153 @@
+use crate::http::TimedResolver;
+use crate::types::{AppError, Endpoint, ErrorKind, TimeoutConfig};
+use hyper::client::connect::HttpInfo;
+use reqwest::{Client, Response};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long an idle pooled connection is kept open for the next request
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// HTTP clients shared by every request, kept in Tauri state so connections are
+/// pooled and kept alive between requests. reqwest only sets the connect timeout
+/// per client, so there is one client per distinct connect timeout.
+#[derive(Default)]
+pub struct HttpClient {
+    clients: Mutex<HashMap<Option<u64>, Client>>,
+    /// Connections that have carried a response, with when one last finished on them
+    connections: Mutex<HashMap<ConnectionId, Instant>>,
+}
+
+impl HttpClient {
+    /// Client for an endpoint, built on first use with its connect timeout
+    pub fn for_endpoint(&self, endpoint: &Endpoint) -> Result<Client, String> {
+        let connect_ms = endpoint.timeouts.connect_ms;
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(client) = clients.get(&connect_ms) {
+            return Ok(client.clone());
+        }
+
+        let mut builder = Client::builder()
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .dns_resolver(Arc::new(TimedResolver));
+        if let Some(connect_ms) = connect_ms {
+            builder = builder.connect_timeout(Duration::from_millis(connect_ms));
+        }
+        let client = builder
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+        clients.insert(connect_ms, client.clone());
+        Ok(client)
+    }
+
+    /// Whether a response arrived on a connection that had already carried one, so
+    /// its timings include no connection setup. `None` when the connection's addresses
+    /// are unknown.
+    pub fn connection_reused(&self, response: &Response) -> Option<bool> {
+        let id = connection_id(response)?;
+        let now = Instant::now();
+        let mut connections = self.connections.lock().unwrap_or_else(|e| e.into_inner());
+        // The pool has closed connections idle for longer than its timeout, so their
+        // addresses may since have been given to new ones
+        connections.retain(|_, last_used| now.duration_since(*last_used) < POOL_IDLE_TIMEOUT);
+        Some(connections.insert(id, now).is_some())
+    }
+
+    /// Record that a response finished on a connection, which goes back to the pool
+    /// and starts idling from now
+    pub fn connection_released(&self, id: ConnectionId) {
+        self.connections
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id, Instant::now());
+    }
+}
+
+/// A connection, told apart from the others while it is open by its local and remote
+/// addresses
+pub type ConnectionId = (SocketAddr, SocketAddr);
+
+pub fn connection_id(response: &Response) -> Option<ConnectionId> {
+    let info = response.extensions().get::<HttpInfo>()?;
+    Some((info.local_addr(), info.remote_addr()))
+}
+
+/// Timeouts of one request, with the total timeout counted from when it was sent
+pub struct Deadlines {
+    config: TimeoutConfig,
+    start: Instant,
+}
+
+impl Deadlines {
+    pub fn new(config: &TimeoutConfig, start: Instant) -> Self {
+        Self {
+            config: config.clone(),
+            start,
+        }
+    }
+
+    /// Await `future`, failing when `limit_ms` or the total timeout runs out first.
+    /// `waiting_for` names what was being waited for in the error.
+    pub async fn run<F: Future>(
+        &self,
+        limit_ms: Option<u64>,
+        waiting_for: &str,
+        future: F,
+    ) -> Result<F::Output, AppError> {
+        let total_left = self
+            .config
+            .total_ms
+            .map(|total| Duration::from_millis(total).saturating_sub(self.start.elapsed()));
+        let limit = limit_ms.map(Duration::from_millis);
+
+        let (wait, error) = match (limit, total_left) {
+            (Some(limit), Some(left)) if left < limit => (left, self.total_error()),
+            (Some(limit), _) => (
+                limit,
+                format!(
+                    "Timed out after {} ms waiting for {}",
+                    limit.as_millis(),
+                    waiting_for
+                ),
+            ),
+            (None, Some(left)) => (left, self.total_error()),
+            (None, None) => return Ok(future.await),
+        };
+
+        tokio::time::timeout(wait, future)
+            .await
+            .map_err(|_| AppError::new(ErrorKind::Timeout, error))
+    }
+
+    fn total_error(&self) -> String {
+        format!(
+            "Request exceeded its total timeout of {} ms",
+            self.config.total_ms.unwrap_or_default()
+        )
+    }
+}
+
+/// Describe a failed send, naming the connect timeout when that is what expired
+pub fn send_error(error: reqwest::Error, timeouts: &TimeoutConfig) -> AppError {
+    let kind = if error.is_timeout() {
+        ErrorKind::Timeout
+    } else if error.is_connect() || error.is_request() {
+        ErrorKind::Connection
+    } else if error.is_builder() {
+        ErrorKind::InvalidRequest
+    } else {
+        ErrorKind::Other
+    };
+
+    match timeouts.connect_ms {
+        Some(connect_ms) if error.is_connect() && error.is_timeout() => AppError::new(
+            kind,
+            format!("Connection timed out after {} ms", connect_ms),
+        ),
+        _ => AppError::new(kind, format!("Request failed: {}", error)),
+    }
+}
@@ -1,27 +1,264 @@
-use crate::http::{send_llm_request, send_llm_request_streaming, get_available_models, test_endpoint};
+use crate::batch;
+use crate::bench;
+use crate::cancel::{CancelToken, RequestRegistry};
+use crate::client::HttpClient;
+use crate::compare;
+use crate::history::{self, record_request, Outcome};
+use crate::http::{
+    get_available_models, send_llm_request, send_llm_request_streaming, test_endpoint, StreamSink,
+};
+use crate::sessions;
 use crate::types::*;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::State;
 
+/// Send a request. With a `session_id`, the session's turns are sent ahead of
+/// `request.messages`, and the new messages plus the reply are stored in the session.
 #[tauri::command]
 pub async fn send_request(
     endpoint: Endpoint,
     request: LLMRequest,
-) -> Result<LLMResponse, String> {
-    send_llm_request(&endpoint, &request).await
+    request_id: Option<String>,
+    session_id: Option<String>,
+    registry: State<'_, RequestRegistry>,
+    http: State<'_, HttpClient>,
+) -> Result<LLMResponse, AppError> {
+    let request_id = request_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let (request, sent) = match &session_id {
+        Some(id) => (sessions::session_request(id, &request)?, request.messages),
+        None => (request, Vec::new()),
+    };
+    let cancel = registry.register(&request_id);
+
+    let mut result = execute_request(&http, &endpoint, &request, &request_id, &cancel).await;
+
+    registry.unregister(&request_id);
+
+    if let (Some(id), Ok(response)) = (&session_id, &mut result) {
+        if !response.cancelled {
+            let reply = sessions::response_turn(&request_id, &request.model, response);
+            if let Err(e) = sessions::record_exchange(id, &sent, reply) {
+                response.warnings.push(session_warning(id, e));
+            }
+        }
+    }
+
+    result
 }
 
+/// Streaming counterpart of `send_request`; tokens are emitted as `token` events
 #[tauri::command]
 pub async fn send_streaming_request(
     endpoint: Endpoint,
     request: LLMRequest,
+    request_id: Option<String>,
+    session_id: Option<String>,
+    registry: State<'_, RequestRegistry>,
+    http: State<'_, HttpClient>,
+    app_handle: tauri::AppHandle,
+) -> Result<StreamingResult, AppError> {
+    let request_id = request_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let (request, sent) = match &session_id {
+        Some(id) => (sessions::session_request(id, &request)?, request.messages),
+        None => (request, Vec::new()),
+    };
+    let cancel = registry.register(&request_id);
+
+    let mut result = execute_streaming_request(
+        &http,
+        &endpoint,
+        &request,
+        &request_id,
+        &cancel,
+        &app_handle,
+    )
+    .await;
+
+    registry.unregister(&request_id);
+
+    if let (Some(id), Ok(streamed)) = (&session_id, &mut result) {
+        if !streamed.cancelled {
+            let reply = sessions::streaming_turn(&request.model, streamed);
+            if let Err(e) = sessions::record_exchange(id, &sent, reply) {
+                streamed.warnings.push(session_warning(id, e));
+            }
+        }
+    }
+
+    result
+}
+
+fn session_warning(id: &str, error: String) -> String {
+    format!("The exchange was not saved to session {}: {}", id, error)
+}
+
+/// Report a failure to save a request to history with its outcome, so the request
+/// itself still succeeds (or fails with its own error)
+fn history_warning<T>(
+    result: &mut Result<T, AppError>,
+    recorded: Result<(), String>,
+    warnings: impl FnOnce(&mut T) -> &mut Vec<String>,
+) {
+    let Err(e) = recorded else {
+        return;
+    };
+    let warning = format!("The request was not saved to history: {}", e);
+    match result {
+        Ok(outcome) => warnings(outcome).push(warning),
+        Err(error) => error.add_warning(warning),
+    }
+}
+
+/// Send a non-streaming request and record it in history
+pub(crate) async fn execute_request(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    request: &LLMRequest,
+    request_id: &str,
+    cancel: &CancelToken,
+) -> Result<LLMResponse, AppError> {
+    let start = Instant::now();
+    let mut result = send_llm_request(http, endpoint, request, cancel).await;
+
+    let outcome = match &result {
+        Ok(response) => Outcome::Response(response),
+        Err(e) => Outcome::Failed(e, start.elapsed()),
+    };
+    let recorded = record_request(request_id, endpoint, request, outcome);
+    history_warning(&mut result, recorded, |response| &mut response.warnings);
+
+    result
+}
+
+/// Send a streaming request, forwarding output to `sink`, and record it in history
+pub(crate) async fn execute_streaming_request(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    request: &LLMRequest,
+    request_id: &str,
+    cancel: &CancelToken,
+    sink: &dyn StreamSink,
+) -> Result<StreamingResult, AppError> {
+    let start = Instant::now();
+    let mut result =
+        send_llm_request_streaming(http, endpoint, request, request_id, cancel, sink).await;
+
+    let outcome = match &result {
+        Ok(streamed) => Outcome::Streamed(streamed),
+        Err(e) => Outcome::Failed(e, start.elapsed()),
+    };
+    let recorded = record_request(request_id, endpoint, request, outcome);
+    history_warning(&mut result, recorded, |streamed| &mut streamed.warnings);
+
+    result
+}
+
+/// Load-test an endpoint, emitting `benchmark_progress` events. Cancel the whole run
+/// with `cancel_request(benchmark_id)`.
+#[tauri::command]
+pub async fn run_benchmark(
+    endpoint: Endpoint,
+    request: LLMRequest,
+    config: BenchmarkConfig,
+    benchmark_id: Option<String>,
+    registry: State<'_, RequestRegistry>,
+    http: State<'_, HttpClient>,
+    app_handle: tauri::AppHandle,
+) -> Result<BenchmarkReport, AppError> {
+    let benchmark_id = benchmark_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let cancel = registry.register(&benchmark_id);
+
+    let result = bench::run_benchmark(
+        &http,
+        &endpoint,
+        &request,
+        &config,
+        &benchmark_id,
+        &cancel,
+        &app_handle,
+    )
+    .await;
+
+    registry.unregister(&benchmark_id);
+    result.map_err(AppError::from)
+}
+
+/// Replay a JSONL file against an endpoint, emitting `batch_progress` per line. Results
+/// go to `output_path`, defaulting to `<input>.results.jsonl`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn run_batch(
+    endpoint: Endpoint,
+    input_path: String,
+    output_path: Option<String>,
+    config: BatchConfig,
+    batch_id: Option<String>,
+    registry: State<'_, RequestRegistry>,
+    http: State<'_, HttpClient>,
+    app_handle: tauri::AppHandle,
+) -> Result<BatchSummary, AppError> {
+    let input = Path::new(&input_path);
+    let output = output_path
+        .map(PathBuf::from)
+        .unwrap_or_else(|| batch::default_output_path(input));
+
+    let batch_id = batch_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let cancel = registry.register(&batch_id);
+
+    let result = batch::run_batch(
+        &http,
+        &endpoint,
+        input,
+        &output,
+        &config,
+        &batch_id,
+        &cancel,
+        &app_handle,
+    )
+    .await;
+
+    registry.unregister(&batch_id);
+    result.map_err(AppError::from)
+}
+
+/// Send one request to several endpoints at once. Streamed tokens arrive on
+/// `compare_token` tagged with the endpoint's index; cancel with `cancel_request(compare_id)`.
+#[tauri::command]
+pub async fn compare_request(
+    endpoints: Vec<Endpoint>,
+    request: LLMRequest,
+    compare_id: Option<String>,
+    registry: State<'_, RequestRegistry>,
+    http: State<'_, HttpClient>,
     app_handle: tauri::AppHandle,
-) -> Result<String, String> {
-    send_llm_request_streaming(&endpoint, &request, app_handle).await
+) -> Result<CompareResult, AppError> {
+    let compare_id = compare_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let cancel = registry.register(&compare_id);
+
+    let result = compare::run_compare(
+        &http,
+        &endpoints,
+        &request,
+        &compare_id,
+        &cancel,
+        &app_handle,
+    )
+    .await;
+
+    registry.unregister(&compare_id);
+    result.map_err(AppError::from)
+}
+
+/// Abort an in-flight request; its partial result is returned by the original call
+#[tauri::command]
+pub fn cancel_request(request_id: String, registry: State<'_, RequestRegistry>) -> Result<bool, AppError> {
+    Ok(registry.cancel(&request_id))
 }
 
 #[tauri::command]
-pub fn get_saved_endpoints() -> Result<Vec<Endpoint>, String> {
+pub fn get_saved_endpoints() -> Result<Vec<Endpoint>, AppError> {
     let config_dir = get_config_dir()?;
     let endpoints_file = config_dir.join("endpoints.json");
 
@@ -39,7 +276,7 @@ pub fn get_saved_endpoints() -> Result<Vec<Endpoint>, String> {
 }
 
 #[tauri::command]
-pub fn save_endpoint(endpoint: Endpoint) -> Result<(), String> {
+pub fn save_endpoint(endpoint: Endpoint) -> Result<(), AppError> {
     let config_dir = get_config_dir()?;
     let endpoints_file = config_dir.join("endpoints.json");
 
@@ -62,7 +299,7 @@ pub fn save_endpoint(endpoint: Endpoint) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn delete_endpoint(id: String) -> Result<(), String> {
+pub fn delete_endpoint(id: String) -> Result<(), AppError> {
     let config_dir = get_config_dir()?;
     let endpoints_file = config_dir.join("endpoints.json");
 
@@ -79,45 +316,64 @@ pub fn delete_endpoint(id: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn get_request_history() -> Result<Vec<RequestHistoryItem>, String> {
-    let config_dir = get_config_dir()?;
-    let history_file = config_dir.join("history.json");
+pub fn get_request_history(include_archived: Option<bool>) -> Result<Vec<RequestHistoryItem>, AppError> {
+    Ok(history::load_history(include_archived.unwrap_or(false))?)
+}
 
-    if !history_file.exists() {
-        return Ok(vec![]);
-    }
+#[tauri::command]
+pub fn clear_history() -> Result<(), AppError> {
+    Ok(history::clear_history()?)
+}
 
-    let content = fs::read_to_string(history_file)
-        .map_err(|e| format!("Failed to read history: {}", e))?;
+#[tauri::command]
+pub fn create_session(title: Option<String>, system_message: Option<String>) -> Result<Session, AppError> {
+    Ok(sessions::create_session(title, system_message)?)
+}
 
-    let history: Vec<RequestHistoryItem> = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse history: {}", e))?;
+#[tauri::command]
+pub fn get_session(session_id: String) -> Result<Session, AppError> {
+    Ok(sessions::get_session(&session_id)?)
+}
 
-    Ok(history)
+#[tauri::command]
+pub fn list_sessions() -> Result<Vec<SessionSummary>, AppError> {
+    Ok(sessions::list_sessions()?)
 }
 
+/// Append messages to a session without sending them
 #[tauri::command]
-pub fn clear_history() -> Result<(), String> {
-    let config_dir = get_config_dir()?;
-    let history_file = config_dir.join("history.json");
+pub fn append_to_session(session_id: String, messages: Vec<Message>) -> Result<Session, AppError> {
+    Ok(sessions::append_messages(&session_id, &messages)?)
+}
 
-    fs::write(history_file, "[]")
-        .map_err(|e| format!("Failed to clear history: {}", e))?;
+/// Branch a session, keeping turns up to and including `turn_index` (all when omitted)
+#[tauri::command]
+pub fn fork_session(session_id: String, turn_index: Option<usize>) -> Result<Session, AppError> {
+    Ok(sessions::fork_session(&session_id, turn_index)?)
+}
 
-    Ok(())
+#[tauri::command]
+pub fn delete_session(session_id: String) -> Result<(), AppError> {
+    Ok(sessions::delete_session(&session_id)?)
 }
 
 #[tauri::command]
-pub async fn fetch_models(endpoint: Endpoint) -> Result<Vec<String>, String> {
-    get_available_models(&endpoint).await
+pub async fn fetch_models(
+    endpoint: Endpoint,
+    http: State<'_, HttpClient>,
+) -> Result<Vec<String>, AppError> {
+    get_available_models(&http, &endpoint).await
 }
 
 #[tauri::command]
-pub async fn test_connection(endpoint: Endpoint) -> Result<String, String> {
-    test_endpoint(&endpoint).await
+pub async fn test_connection(
+    endpoint: Endpoint,
+    http: State<'_, HttpClient>,
+) -> Result<String, AppError> {
+    test_endpoint(&http, &endpoint).await
 }
 
-fn get_config_dir() -> Result<PathBuf, String> {
+pub(crate) fn get_config_dir() -> Result<PathBuf, String> {
     let config_dir = dirs::config_dir()
         .ok_or_else(|| "Failed to get config directory".to_string())?
         .join("modelcurl");
@@ -0,0 +1,188 @@
+use crate::cancel::CancelToken;
+use crate::client::HttpClient;
+use crate::commands::{execute_request, execute_streaming_request};
+use crate::diff::{diff_words, similarity};
+use crate::http::{reasoning_text, StreamSink};
+use crate::metrics::failed_request_metrics;
+use crate::types::{CompareEntry, CompareResult, CompareToken, ContentDiff, Endpoint, LLMRequest};
+use std::time::Instant;
+
+/// Receives tokens from every endpoint in a comparison, tagged with their origin
+pub trait CompareSink: Send + Sync {
+    fn on_token(&self, token: &CompareToken) -> Result<(), String>;
+}
+
+impl CompareSink for tauri::AppHandle {
+    fn on_token(&self, token: &CompareToken) -> Result<(), String> {
+        use tauri::Manager;
+
+        self.emit_all("compare_token", token)
+            .map_err(|e| format!("Failed to emit compare token: {}", e))
+    }
+}
+
+/// Forwards one endpoint's stream to a `CompareSink`
+struct TaggedSink<'a> {
+    compare_id: &'a str,
+    index: usize,
+    endpoint_id: &'a str,
+    sink: &'a dyn CompareSink,
+}
+
+impl TaggedSink<'_> {
+    fn emit(&self, token: &str, reasoning: bool) -> Result<(), String> {
+        self.sink.on_token(&CompareToken {
+            compare_id: self.compare_id.to_string(),
+            index: self.index,
+            endpoint_id: self.endpoint_id.to_string(),
+            token: token.to_string(),
+            reasoning,
+        })
+    }
+}
+
+impl StreamSink for TaggedSink<'_> {
+    fn on_token(&self, token: &str) -> Result<(), String> {
+        self.emit(token, false)
+    }
+
+    fn on_reasoning_token(&self, token: &str) -> Result<(), String> {
+        self.emit(token, true)
+    }
+}
+
+/// Send `request` to every endpoint at once. Each endpoint's own model replaces the
+/// request's (unless it is empty), and one endpoint failing does not fail the rest.
+pub async fn run_compare(
+    http: &HttpClient,
+    endpoints: &[Endpoint],
+    request: &LLMRequest,
+    compare_id: &str,
+    cancel: &CancelToken,
+    sink: &dyn CompareSink,
+) -> Result<CompareResult, String> {
+    if endpoints.len() < 2 {
+        return Err("Pick at least two endpoints to compare".to_string());
+    }
+
+    let entries =
+        futures_util::future::join_all(endpoints.iter().enumerate().map(|(index, endpoint)| {
+            run_entry(http, endpoint, index, request, compare_id, cancel, sink)
+        }))
+        .await;
+
+    let diffs = diff_entries(&entries);
+
+    Ok(CompareResult {
+        compare_id: compare_id.to_string(),
+        entries,
+        diffs,
+    })
+}
+
+async fn run_entry(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    index: usize,
+    request: &LLMRequest,
+    compare_id: &str,
+    cancel: &CancelToken,
+    sink: &dyn CompareSink,
+) -> CompareEntry {
+    let mut request = request.clone();
+    if !endpoint.model.is_empty() {
+        request.model = endpoint.model.clone();
+    }
+
+    let request_id = format!("{}-{}", compare_id, index);
+    let mut entry = CompareEntry {
+        index,
+        endpoint_id: endpoint.id.clone(),
+        endpoint_name: endpoint.name.clone(),
+        model: request.model.clone(),
+        success: false,
+        error: None,
+        cancelled: false,
+        content: String::new(),
+        reasoning_content: None,
+        usage: None,
+        metrics: Default::default(),
+        warnings: Vec::new(),
+    };
+
+    let start = Instant::now();
+
+    let result = if request.stream {
+        let tagged = TaggedSink {
+            compare_id,
+            index,
+            endpoint_id: &endpoint.id,
+            sink,
+        };
+        execute_streaming_request(http, endpoint, &request, &request_id, cancel, &tagged)
+            .await
+            .map(|r| {
+                let reasoning = reasoning_text(r.reasoning_content, &r.thinking_blocks);
+                (
+                    r.content,
+                    reasoning,
+                    r.usage,
+                    r.metrics,
+                    r.cancelled,
+                    r.warnings,
+                )
+            })
+    } else {
+        execute_request(http, endpoint, &request, &request_id, cancel)
+            .await
+            .map(|r| {
+                let reasoning = reasoning_text(r.reasoning_content, &r.thinking_blocks);
+                (
+                    r.content,
+                    reasoning,
+                    r.usage,
+                    r.metrics,
+                    r.cancelled,
+                    r.warnings,
+                )
+            })
+    };
+
+    match result {
+        Ok((content, reasoning_content, usage, metrics, cancelled, warnings)) => {
+            entry.success = !cancelled;
+            entry.cancelled = cancelled;
+            entry.content = content;
+            entry.reasoning_content = reasoning_content;
+            entry.usage = usage;
+            entry.metrics = metrics;
+            entry.warnings = warnings;
+        }
+        Err(e) => {
+            entry.metrics = failed_request_metrics(start.elapsed(), &e);
+            entry.error = Some(e);
+        }
+    }
+
+    entry
+}
+
+/// Diff every successful entry against the first successful one
+fn diff_entries(entries: &[CompareEntry]) -> Vec<ContentDiff> {
+    let mut successes = entries.iter().filter(|e| e.success);
+    let Some(baseline) = successes.next() else {
+        return Vec::new();
+    };
+
+    successes
+        .map(|entry| {
+            let segments = diff_words(&baseline.content, &entry.content);
+            ContentDiff {
+                baseline_index: baseline.index,
+                index: entry.index,
+                similarity: similarity(&segments),
+                segments,
+            }
+        })
+        .collect()
+}
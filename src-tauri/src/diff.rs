@@ -0,0 +1,176 @@
+use crate::types::{DiffOp, DiffSegment};
+
+/// Largest LCS table (in cells) diffed word by word; larger inputs that differ
+/// past their common prefix/suffix are reported as one delete plus one insert
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Split text into alternating runs of whitespace and non-whitespace so that
+/// concatenating the tokens reproduces the input exactly
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_whitespace = None;
+
+    for (i, c) in text.char_indices() {
+        let ws = c.is_whitespace();
+        if in_whitespace.is_some_and(|prev| prev != ws) {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+        in_whitespace = Some(ws);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+
+    tokens
+}
+
+/// Word-level diff of `new` against `old`, with consecutive tokens of the same
+/// kind merged into segments
+pub fn diff_words(old: &str, new: &str) -> Vec<DiffSegment> {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+
+    let mut segments: Vec<DiffSegment> = Vec::new();
+    for (op, token) in diff_tokens(&old_tokens, &new_tokens) {
+        match segments.last_mut() {
+            Some(last) if last.op == op => last.text.push_str(token),
+            _ => segments.push(DiffSegment {
+                op,
+                text: token.to_string(),
+            }),
+        }
+    }
+    segments
+}
+
+/// Share of text the two sides have in common, from 0.0 to 1.0
+pub fn similarity(segments: &[DiffSegment]) -> f64 {
+    let mut common = 0;
+    let mut total = 0;
+    for segment in segments {
+        let len = segment.text.chars().count();
+        match segment.op {
+            DiffOp::Equal => {
+                common += 2 * len;
+                total += 2 * len;
+            }
+            DiffOp::Insert | DiffOp::Delete => total += len,
+        }
+    }
+
+    if total == 0 {
+        1.0
+    } else {
+        common as f64 / total as f64
+    }
+}
+
+fn diff_tokens<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(DiffOp, &'a str)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<(DiffOp, &str)> = old[..prefix].iter().map(|t| (DiffOp::Equal, *t)).collect();
+
+    if (a.len() + 1) * (b.len() + 1) > MAX_DIFF_CELLS {
+        ops.extend(a.iter().map(|t| (DiffOp::Delete, *t)));
+        ops.extend(b.iter().map(|t| (DiffOp::Insert, *t)));
+    } else {
+        // lcs[i][j] = length of the longest common subsequence of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lcs = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            if a[i] == b[j] {
+                ops.push((DiffOp::Equal, a[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                ops.push((DiffOp::Delete, a[i]));
+                i += 1;
+            } else {
+                ops.push((DiffOp::Insert, b[j]));
+                j += 1;
+            }
+        }
+        ops.extend(a[i..].iter().map(|t| (DiffOp::Delete, *t)));
+        ops.extend(b[j..].iter().map(|t| (DiffOp::Insert, *t)));
+    }
+
+    ops.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|t| (DiffOp::Equal, *t)),
+    );
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(segments: &[DiffSegment]) -> String {
+        segments
+            .iter()
+            .map(|s| match s.op {
+                DiffOp::Equal => s.text.clone(),
+                DiffOp::Insert => format!("{{+{}+}}", s.text),
+                DiffOp::Delete => format!("[-{}-]", s.text),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_roundtrip() {
+        let text = "  Hello,\tworld!\n\nBye ";
+        assert_eq!(tokenize(text).concat(), text);
+        assert_eq!(tokenize("a b"), vec!["a", " ", "b"]);
+    }
+
+    #[test]
+    fn test_identical_text() {
+        let segments = diff_words("the same text", "the same text");
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].op, DiffOp::Equal);
+        assert_eq!(similarity(&segments), 1.0);
+    }
+
+    #[test]
+    fn test_word_change() {
+        let segments = diff_words("The sky is blue today", "The sky is grey today");
+        assert_eq!(render(&segments), "The sky is [-blue-]{+grey+} today");
+    }
+
+    #[test]
+    fn test_insert_and_delete() {
+        let segments = diff_words("a b c", "a c d");
+        assert_eq!(render(&segments), "a [-b -]c{+ d+}");
+    }
+
+    #[test]
+    fn test_empty_sides() {
+        assert_eq!(render(&diff_words("", "new")), "{+new+}");
+        assert_eq!(render(&diff_words("old", "")), "[-old-]");
+        assert_eq!(similarity(&diff_words("", "")), 1.0);
+        assert_eq!(similarity(&diff_words("abc", "xyz")), 0.0);
+    }
+}
@@ -0,0 +1,348 @@
+use crate::types::{AppError, ErrorKind};
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::fmt;
+
+impl AppError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            status: None,
+            provider_code: None,
+            provider_message: None,
+            headers: Vec::new(),
+            retryable: is_retryable(kind, None),
+            retries: None,
+            warnings: None,
+        }
+    }
+
+    /// Error for a non-success response, e.g. `from_response("Request failed", ...)`.
+    /// The body is parsed as an OpenAI, Anthropic, vLLM or Gemini error; anything else
+    /// is quoted as is.
+    pub fn from_response(
+        context: &str,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &str,
+    ) -> Self {
+        let (code, provider_message) = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|parsed| {
+                let error = error_object(&parsed).or_else(|| parsed.get("detail"))?;
+                Some(provider_error(error))
+            })
+            .unwrap_or_default();
+        let kind = classify(
+            Some(status.as_u16()),
+            code.as_deref(),
+            provider_message.as_deref(),
+        );
+
+        Self {
+            kind,
+            message: format!(
+                "{} with status {}: {}",
+                context,
+                status,
+                provider_message.as_deref().unwrap_or(body)
+            ),
+            status: Some(status.as_u16()),
+            retryable: is_retryable(kind, code.as_deref()),
+            provider_code: code,
+            provider_message,
+            headers: headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            retries: None,
+            warnings: None,
+        }
+    }
+
+    /// Error sent as an event of an otherwise successful stream (Anthropic's `error`
+    /// event, OpenAI and vLLM `{"error": ...}` chunks, Responses `response.failed`).
+    /// `None` for any other event.
+    pub fn from_stream_event(event: &Value) -> Option<Self> {
+        let (code, provider_message) = provider_error(error_object(event)?);
+        let kind = classify(None, code.as_deref(), provider_message.as_deref());
+
+        Some(Self {
+            kind,
+            message: format!(
+                "Stream error: {}",
+                provider_message.as_deref().unwrap_or("unknown error")
+            ),
+            status: None,
+            retryable: is_retryable(kind, code.as_deref()),
+            provider_code: code,
+            provider_message,
+            headers: Vec::new(),
+            retries: None,
+            warnings: None,
+        })
+    }
+
+    /// Report a problem alongside the failure without changing its message
+    pub fn add_warning(&mut self, warning: String) {
+        self.warnings.get_or_insert_with(Box::default).push(warning);
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Local failures (configuration, storage, adapters) are still reported as text
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorKind::Other, message)
+    }
+}
+
+/// For callers that only report the message: the CLI, batch and benchmark results
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.message
+    }
+}
+
+/// The part of a body that describes the error, if it is an error body
+fn error_object(body: &Value) -> Option<&Value> {
+    match &body["error"] {
+        // OpenAI, Anthropic, Gemini and recent vLLM nest it; vLLM's API key check
+        // answers `{"error": "Unauthorized"}`
+        error @ (Value::Object(_) | Value::String(_)) => Some(error),
+        // Older vLLM: `{"object": "error", "message": ..., "type": ..., "code": 400}`
+        _ if body["object"] == "error" || body["type"] == "error" => Some(body),
+        _ if body["type"] == "response.failed" => Some(&body["response"]["error"]),
+        _ => None,
+    }
+}
+
+/// Code and message of an error object. The code is the most specific of `code`
+/// (OpenAI, Responses), `status` (Gemini) and `type` (Anthropic, vLLM); vLLM's numeric
+/// `code` only repeats the HTTP status.
+fn provider_error(error: &Value) -> (Option<String>, Option<String>) {
+    if let Some(message) = error.as_str() {
+        return (None, Some(message.to_string()));
+    }
+
+    let code = [&error["code"], &error["status"], &error["type"]]
+        .into_iter()
+        .filter_map(Value::as_str)
+        .find(|code| *code != "error")
+        .map(str::to_string);
+    let message = error["message"].as_str().map(str::to_string);
+    (code, message)
+}
+
+fn classify(status: Option<u16>, code: Option<&str>, message: Option<&str>) -> ErrorKind {
+    let code = code.unwrap_or_default().to_ascii_lowercase();
+    let message = message.unwrap_or_default().to_ascii_lowercase();
+
+    if is_context_overflow(&code, &message) {
+        return ErrorKind::ContextLength;
+    }
+
+    match status {
+        Some(401 | 403) => ErrorKind::Auth,
+        Some(404) => ErrorKind::NotFound,
+        Some(408) => ErrorKind::Timeout,
+        Some(429) => ErrorKind::RateLimit,
+        Some(500..) => ErrorKind::Server,
+        Some(400..) => ErrorKind::InvalidRequest,
+        // Stream errors have no status of their own
+        _ => match code.as_str() {
+            "authentication_error"
+            | "permission_error"
+            | "invalid_api_key"
+            | "unauthenticated"
+            | "permission_denied" => ErrorKind::Auth,
+            "not_found_error" | "model_not_found" | "not_found" => ErrorKind::NotFound,
+            "rate_limit_error"
+            | "rate_limit_exceeded"
+            | "insufficient_quota"
+            | "resource_exhausted" => ErrorKind::RateLimit,
+            "invalid_request_error" | "invalid_argument" | "badrequesterror" => {
+                ErrorKind::InvalidRequest
+            }
+            "overloaded_error"
+            | "api_error"
+            | "server_error"
+            | "internal"
+            | "unavailable"
+            | "internalservererror" => ErrorKind::Server,
+            _ => ErrorKind::Other,
+        },
+    }
+}
+
+/// The wording each server uses for a prompt that does not fit the context window
+fn is_context_overflow(code: &str, message: &str) -> bool {
+    const PHRASES: [&str; 5] = [
+        // OpenAI and vLLM: "This model's maximum context length is 4096 tokens..."
+        "maximum context length",
+        // OpenAI Responses: "Your input exceeds the context window of this model"
+        "context window",
+        // Anthropic: "prompt is too long: 208000 tokens > 200000 maximum"
+        "prompt is too long",
+        // vLLM: "The decoder prompt (length 5000) is longer than the maximum model length"
+        "maximum model length",
+        // Gemini: "The input token count (1200000) exceeds the maximum number of tokens"
+        "input token count",
+    ];
+
+    code == "context_length_exceeded" || PHRASES.iter().any(|phrase| message.contains(phrase))
+}
+
+/// Whether the same request may succeed later. A used-up quota is a 429 too, but
+/// waiting does not help.
+fn is_retryable(kind: ErrorKind, code: Option<&str>) -> bool {
+    matches!(
+        kind,
+        ErrorKind::RateLimit | ErrorKind::Server | ErrorKind::Timeout | ErrorKind::Connection
+    ) && code != Some("insufficient_quota")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    fn response(status: u16, body: &str) -> AppError {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", HeaderValue::from_static("req_123"));
+        AppError::from_response(
+            "Request failed",
+            StatusCode::from_u16(status).unwrap(),
+            &headers,
+            body,
+        )
+    }
+
+    #[test]
+    fn test_openai_errors() {
+        let error = response(
+            400,
+            r#"{"error": {"message": "This model's maximum context length is 128000 tokens. However, your messages resulted in 130000 tokens.", "type": "invalid_request_error", "param": "messages", "code": "context_length_exceeded"}}"#,
+        );
+        assert_eq!(error.kind, ErrorKind::ContextLength);
+        assert_eq!(
+            error.provider_code.as_deref(),
+            Some("context_length_exceeded")
+        );
+        assert!(!error.retryable);
+        assert_eq!(
+            error.headers,
+            vec![("x-request-id".to_string(), "req_123".to_string())]
+        );
+        assert!(error.message.starts_with(
+            "Request failed with status 400 Bad Request: This model's maximum context length"
+        ));
+
+        let error = response(
+            401,
+            r#"{"error": {"message": "Incorrect API key provided", "type": "invalid_request_error", "param": null, "code": "invalid_api_key"}}"#,
+        );
+        assert_eq!(error.kind, ErrorKind::Auth);
+        assert_eq!(error.status, Some(401));
+
+        let error = response(
+            429,
+            r#"{"error": {"message": "You exceeded your current quota", "type": "insufficient_quota", "param": null, "code": "insufficient_quota"}}"#,
+        );
+        assert_eq!(error.kind, ErrorKind::RateLimit);
+        assert!(!error.retryable);
+
+        let error = response(
+            429,
+            r#"{"error": {"message": "Rate limit reached for requests", "type": "requests", "param": null, "code": "rate_limit_exceeded"}}"#,
+        );
+        assert!(error.retryable);
+    }
+
+    #[test]
+    fn test_anthropic_errors() {
+        let error = response(
+            529,
+            r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#,
+        );
+        assert_eq!(error.kind, ErrorKind::Server);
+        assert_eq!(error.provider_code.as_deref(), Some("overloaded_error"));
+        assert_eq!(error.provider_message.as_deref(), Some("Overloaded"));
+        assert!(error.retryable);
+
+        let error = response(
+            400,
+            r#"{"type": "error", "error": {"type": "invalid_request_error", "message": "prompt is too long: 208000 tokens > 200000 maximum"}}"#,
+        );
+        assert_eq!(error.kind, ErrorKind::ContextLength);
+    }
+
+    #[test]
+    fn test_vllm_errors() {
+        // Older releases answer with a flat error object
+        let error = response(
+            400,
+            r#"{"object": "error", "message": "This model's maximum context length is 4096 tokens. However, you requested 5000 tokens.", "type": "BadRequestError", "param": null, "code": 400}"#,
+        );
+        assert_eq!(error.kind, ErrorKind::ContextLength);
+        assert_eq!(error.provider_code.as_deref(), Some("BadRequestError"));
+
+        // Newer ones nest it like OpenAI, still with a numeric code
+        let error = response(
+            404,
+            r#"{"error": {"message": "The model `llama` does not exist.", "type": "NotFoundError", "param": null, "code": 404}}"#,
+        );
+        assert_eq!(error.kind, ErrorKind::NotFound);
+        assert_eq!(error.provider_code.as_deref(), Some("NotFoundError"));
+        assert_eq!(
+            error.provider_message.as_deref(),
+            Some("The model `llama` does not exist.")
+        );
+
+        let error = response(401, r#"{"error": "Unauthorized"}"#);
+        assert_eq!(error.kind, ErrorKind::Auth);
+        assert_eq!(error.provider_message.as_deref(), Some("Unauthorized"));
+
+        let error = response(404, r#"{"detail": "Not Found"}"#);
+        assert_eq!(error.provider_message.as_deref(), Some("Not Found"));
+    }
+
+    #[test]
+    fn test_unparsed_body() {
+        let error = response(502, "<html>Bad Gateway</html>");
+        assert_eq!(error.kind, ErrorKind::Server);
+        assert!(error.retryable);
+        assert_eq!(error.provider_code, None);
+        assert_eq!(
+            error.message,
+            "Request failed with status 502 Bad Gateway: <html>Bad Gateway</html>"
+        );
+    }
+
+    #[test]
+    fn test_stream_events() {
+        let anthropic = json!({"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}});
+        let error = AppError::from_stream_event(&anthropic).unwrap();
+        assert_eq!(error.kind, ErrorKind::Server);
+        assert_eq!(error.message, "Stream error: Overloaded");
+
+        let failed = json!({"type": "response.failed", "response": {"error": {"code": "rate_limit_exceeded", "message": "Slow down"}}});
+        assert_eq!(
+            AppError::from_stream_event(&failed).unwrap().kind,
+            ErrorKind::RateLimit
+        );
+
+        let chunk = json!({"choices": [{"delta": {"content": "Hi"}}], "error": null});
+        assert!(AppError::from_stream_event(&chunk).is_none());
+    }
+}
@@ -0,0 +1,405 @@
+use crate::commands::get_config_dir;
+use crate::metrics::failed_request_metrics;
+use crate::types::{
+    AppError, Endpoint, LLMRequest, LLMResponse, PerformanceMetrics, RequestHistoryItem,
+    StreamingResult,
+};
+use lazy_static::lazy_static;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Maximum number of items kept in `history.jsonl` before it is rotated
+const MAX_HISTORY_ITEMS: usize = 500;
+
+/// Number of rotated files kept (`history.1.jsonl` is the most recent)
+const MAX_HISTORY_ARCHIVES: usize = 10;
+
+lazy_static! {
+    /// Number of items in `history.jsonl`, counted on first use so appending never
+    /// reads the file. Also serializes writes from concurrent requests.
+    static ref HISTORY_ITEMS: Mutex<Option<usize>> = Mutex::new(None);
+}
+
+/// History is stored one JSON item per line, so recording a request is a single append
+fn history_file(dir: &Path) -> PathBuf {
+    dir.join("history.jsonl")
+}
+
+fn archive_file(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("history.{}.jsonl", index))
+}
+
+fn read_history_file(path: &Path) -> Result<Vec<RequestHistoryItem>, String> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read history: {}", e))?;
+
+    // A line cut short by a crash mid-append is skipped rather than losing the file
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Convert the JSON arrays older versions wrote (`history.json`, `history.N.json`)
+fn migrate_legacy_files(dir: &Path) -> Result<(), String> {
+    let legacy = std::iter::once((dir.join("history.json"), history_file(dir))).chain(
+        (1..=MAX_HISTORY_ARCHIVES).map(|index| {
+            (
+                dir.join(format!("history.{}.json", index)),
+                archive_file(dir, index),
+            )
+        }),
+    );
+
+    for (from, to) in legacy {
+        if !from.exists() {
+            continue;
+        }
+        if !to.exists() {
+            let content =
+                fs::read_to_string(&from).map_err(|e| format!("Failed to read history: {}", e))?;
+            let items: Vec<RequestHistoryItem> = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse history: {}", e))?;
+            let mut lines = String::new();
+            for item in &items {
+                lines.push_str(&history_line(item)?);
+            }
+            fs::write(&to, lines).map_err(|e| format!("Failed to write history: {}", e))?;
+        }
+        fs::remove_file(&from).map_err(|e| format!("Failed to migrate history: {}", e))?;
+    }
+
+    Ok(())
+}
+
+fn history_line(item: &RequestHistoryItem) -> Result<String, String> {
+    let mut line =
+        serde_json::to_string(item).map_err(|e| format!("Failed to serialize history: {}", e))?;
+    line.push('\n');
+    Ok(line)
+}
+
+fn append_line(path: &Path, line: &str) -> Result<(), String> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("Failed to write history: {}", e))
+}
+
+/// Items in `history.jsonl`, converting legacy files and counting them on first use
+fn current_items(dir: &Path, items: &mut Option<usize>) -> Result<usize, String> {
+    if let Some(count) = *items {
+        return Ok(count);
+    }
+
+    migrate_legacy_files(dir)?;
+    let path = history_file(dir);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read history: {}", e)),
+    };
+    // End a line cut short by a crash, so the next item does not join it
+    if !content.is_empty() && !content.ends_with('\n') {
+        append_line(&path, "\n")?;
+    }
+
+    let count = content
+        .lines()
+        .filter(|line| serde_json::from_str::<RequestHistoryItem>(line).is_ok())
+        .count();
+    *items = Some(count);
+    Ok(count)
+}
+
+/// Shift `history.jsonl` into the archive chain, dropping the oldest archive
+fn rotate(dir: &Path) -> Result<(), String> {
+    let oldest = archive_file(dir, MAX_HISTORY_ARCHIVES);
+    if oldest.exists() {
+        fs::remove_file(&oldest).map_err(|e| format!("Failed to rotate history: {}", e))?;
+    }
+
+    for index in (1..MAX_HISTORY_ARCHIVES).rev() {
+        let from = archive_file(dir, index);
+        if from.exists() {
+            fs::rename(&from, archive_file(dir, index + 1))
+                .map_err(|e| format!("Failed to rotate history: {}", e))?;
+        }
+    }
+
+    fs::rename(history_file(dir), archive_file(dir, 1))
+        .map_err(|e| format!("Failed to rotate history: {}", e))
+}
+
+fn load_from(
+    dir: &Path,
+    items: &mut Option<usize>,
+    include_archived: bool,
+) -> Result<Vec<RequestHistoryItem>, String> {
+    current_items(dir, items)?;
+    let mut history = Vec::new();
+
+    if include_archived {
+        for index in (1..=MAX_HISTORY_ARCHIVES).rev() {
+            history.extend(read_history_file(&archive_file(dir, index))?);
+        }
+    }
+
+    history.extend(read_history_file(&history_file(dir))?);
+
+    Ok(history)
+}
+
+fn append_to(
+    dir: &Path,
+    items: &mut Option<usize>,
+    item: &RequestHistoryItem,
+) -> Result<(), String> {
+    let line = history_line(item)?;
+    let mut count = current_items(dir, items)?;
+    if count >= MAX_HISTORY_ITEMS {
+        rotate(dir)?;
+        count = 0;
+    }
+
+    append_line(&history_file(dir), &line)?;
+
+    *items = Some(count + 1);
+    Ok(())
+}
+
+fn clear_in(dir: &Path, items: &mut Option<usize>) -> Result<(), String> {
+    current_items(dir, items)?;
+
+    for path in (1..=MAX_HISTORY_ARCHIVES)
+        .map(|index| archive_file(dir, index))
+        .chain(std::iter::once(history_file(dir)))
+    {
+        if path.exists() {
+            fs::remove_file(path).map_err(|e| format!("Failed to clear history: {}", e))?;
+        }
+    }
+
+    *items = Some(0);
+    Ok(())
+}
+
+/// Load the current history, optionally preceded by rotated archives (oldest first)
+pub fn load_history(include_archived: bool) -> Result<Vec<RequestHistoryItem>, String> {
+    let mut items = HISTORY_ITEMS.lock().unwrap_or_else(|e| e.into_inner());
+    load_from(&get_config_dir()?, &mut items, include_archived)
+}
+
+/// Append an item, rotating `history.jsonl` once it reaches the size cap
+pub fn append_history(item: &RequestHistoryItem) -> Result<(), String> {
+    let mut items = HISTORY_ITEMS.lock().unwrap_or_else(|e| e.into_inner());
+    append_to(&get_config_dir()?, &mut items, item)
+}
+
+/// Clear the current history and all rotated archives
+pub fn clear_history() -> Result<(), String> {
+    let mut items = HISTORY_ITEMS.lock().unwrap_or_else(|e| e.into_inner());
+    clear_in(&get_config_dir()?, &mut items)
+}
+
+/// How a recorded request ended
+pub enum Outcome<'a> {
+    Response(&'a LLMResponse),
+    Streamed(&'a StreamingResult),
+    /// Failed after the time given
+    Failed(&'a AppError, Duration),
+}
+
+/// Record a completed (or failed) request. A failure to record it is returned for the
+/// caller to report; it never fails the request itself.
+pub fn record_request(
+    request_id: &str,
+    endpoint: &Endpoint,
+    request: &LLMRequest,
+    outcome: Outcome<'_>,
+) -> Result<(), String> {
+    let prompt = request
+        .messages
+        .iter()
+        .rev()
+        .find(|m| m.role == "user")
+        .map(|m| m.content.text())
+        .unwrap_or_default();
+
+    let mut item = RequestHistoryItem {
+        id: request_id.to_string(),
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        endpoint_id: endpoint.id.clone(),
+        endpoint_name: endpoint.name.clone(),
+        endpoint_url: endpoint.url.clone(),
+        model: request.model.clone(),
+        prompt,
+        response: String::new(),
+        metrics: PerformanceMetrics::default(),
+        stream: request.stream,
+        request: Some(request.clone()),
+        llm_response: None,
+        streaming_result: None,
+        cancelled: false,
+        error: None,
+    };
+
+    match outcome {
+        Outcome::Response(response) => {
+            item.response = response.content.clone();
+            item.metrics = response.metrics.clone();
+            item.cancelled = response.cancelled;
+            item.llm_response = Some(response.clone());
+        }
+        Outcome::Streamed(streamed) => {
+            item.response = streamed.content.clone();
+            item.metrics = streamed.metrics.clone();
+            item.cancelled = streamed.cancelled;
+            item.streaming_result = Some(streamed.clone());
+        }
+        Outcome::Failed(error, elapsed) => {
+            item.metrics = failed_request_metrics(elapsed, error);
+            item.error = Some(error.message.clone());
+        }
+    }
+
+    append_history(&item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: usize) -> RequestHistoryItem {
+        RequestHistoryItem {
+            id: id.to_string(),
+            timestamp: id as i64,
+            endpoint_id: "local".to_string(),
+            endpoint_name: "Local".to_string(),
+            endpoint_url: "http://localhost:8000/v1".to_string(),
+            model: "llama".to_string(),
+            prompt: "hi".to_string(),
+            response: "hello".to_string(),
+            metrics: PerformanceMetrics::default(),
+            stream: false,
+            request: None,
+            llm_response: None,
+            streaming_result: None,
+            cancelled: false,
+            error: None,
+        }
+    }
+
+    fn ids(items: &[RequestHistoryItem]) -> Vec<usize> {
+        items.iter().map(|item| item.id.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_rotation() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let mut items = None;
+
+        for id in 0..MAX_HISTORY_ITEMS {
+            append_to(dir, &mut items, &item(id)).unwrap();
+        }
+        assert!(!archive_file(dir, 1).exists());
+
+        // The 501st item starts a new file
+        append_to(dir, &mut items, &item(MAX_HISTORY_ITEMS)).unwrap();
+        assert_eq!(
+            ids(&read_history_file(&archive_file(dir, 1)).unwrap()),
+            (0..MAX_HISTORY_ITEMS).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            ids(&load_from(dir, &mut items, false).unwrap()),
+            vec![MAX_HISTORY_ITEMS]
+        );
+
+        // A restart counts the current file again rather than starting from zero
+        let mut restarted = None;
+        for id in MAX_HISTORY_ITEMS + 1..2 * MAX_HISTORY_ITEMS + 1 {
+            append_to(dir, &mut restarted, &item(id)).unwrap();
+        }
+        assert_eq!(read_history_file(&history_file(dir)).unwrap().len(), 1);
+        assert_eq!(
+            read_history_file(&archive_file(dir, 1)).unwrap().len(),
+            MAX_HISTORY_ITEMS
+        );
+    }
+
+    #[test]
+    fn test_oldest_archive_dropped() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let mut items = None;
+        let total = MAX_HISTORY_ITEMS * (MAX_HISTORY_ARCHIVES + 1) + 1;
+
+        for id in 0..total {
+            append_to(dir, &mut items, &item(id)).unwrap();
+        }
+
+        assert!(!archive_file(dir, MAX_HISTORY_ARCHIVES + 1).exists());
+        let all = ids(&load_from(dir, &mut items, true).unwrap());
+        // The first 500 items went with the dropped archive; the rest are in order
+        assert_eq!(all, (MAX_HISTORY_ITEMS..total).collect::<Vec<_>>());
+
+        clear_in(dir, &mut items).unwrap();
+        assert!(load_from(dir, &mut items, true).unwrap().is_empty());
+        assert!(!archive_file(dir, 1).exists());
+    }
+
+    #[test]
+    fn test_legacy_files_migrated() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let legacy = |ids: std::ops::Range<usize>| {
+            serde_json::to_string_pretty(&ids.map(item).collect::<Vec<_>>()).unwrap()
+        };
+        fs::write(dir.join("history.1.json"), legacy(0..3)).unwrap();
+        fs::write(dir.join("history.json"), legacy(3..5)).unwrap();
+
+        let mut items = None;
+        append_to(dir, &mut items, &item(5)).unwrap();
+
+        assert!(!dir.join("history.json").exists());
+        assert!(!dir.join("history.1.json").exists());
+        assert_eq!(items, Some(3));
+        assert_eq!(
+            ids(&load_from(dir, &mut items, true).unwrap()),
+            vec![0, 1, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn test_torn_line_skipped() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let mut items = None;
+        append_to(dir, &mut items, &item(0)).unwrap();
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(history_file(dir))
+            .unwrap();
+        file.write_all(br#"{"id":"1","timest"#).unwrap();
+
+        let mut restarted = None;
+        assert_eq!(
+            ids(&load_from(dir, &mut restarted, false).unwrap()),
+            vec![0]
+        );
+        append_to(dir, &mut restarted, &item(2)).unwrap();
+        assert_eq!(
+            ids(&load_from(dir, &mut restarted, false).unwrap()),
+            vec![0, 2]
+        );
+    }
+}
@@ -1,360 +1,981 @@
-use crate::types::{Endpoint, LLMRequest, LLMResponse, UsageMetrics, ReasoningProvider};
-use crate::provider::detect_provider;
+use crate::adapters::{resolve_adapter, ProviderAdapter, ToolCallDelta};
+use crate::media::inline_local_media;
+use crate::provider::needs_metadata;
+use crate::retry;
+use crate::types::{
+    AppError, Endpoint, ErrorKind, LLMRequest, LLMResponse, NetworkTimings, PerformanceMetrics,
+    ProviderResolution, RetryStats, RetryableError, StreamingResult, ThinkingBlock, TokenLogprob, ToolCall,
+    UsageMetrics,
+};
+use crate::cancel::CancelToken;
+use crate::client::{connection_id, send_error, ConnectionId, Deadlines, HttpClient};
+use crate::metrics::{as_ms, non_streaming_metrics, MetricsCollector};
+use crate::logprobs::logprob_stats;
+use crate::schema::validate_output;
+use crate::sse::SseDecoder;
 use anyhow::Result;
-use reqwest::Client;
-use std::time::Instant;
+use futures_util::StreamExt;
+use lazy_static::lazy_static;
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::header::CONTENT_LENGTH;
+use reqwest::{Body, RequestBuilder, Response};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use serde_json::Value;
 
-/// Build request body with provider-specific reasoning parameters
-fn build_request_body(_endpoint: &Endpoint, request: &LLMRequest, stream: bool) -> Value {
-    let provider = detect_provider(&request.model);
-
-    let mut body = serde_json::json!({
-        "model": request.model.clone(),
-        "messages": request.messages,
-        "temperature": request.temperature,
-        "stream": stream
-    });
-
-    // Add max_tokens based on provider
-    if let Some(reasoning_config) = &request.reasoning_config {
-        match provider {
-            Some(ReasoningProvider::OpenAI) => {
-                // OpenAI uses max_completion_tokens for reasoning models
-                if let Some(max_completion) = reasoning_config.max_completion_tokens {
-                    body["max_completion_tokens"] = serde_json::json!(max_completion);
-                } else {
-                    body["max_tokens"] = serde_json::json!(request.max_tokens);
-                }
+lazy_static! {
+    /// `/models` listing per endpoint ID and URL, kept for provider detection. Endpoints
+    /// sharing a URL may send different keys and so see different models.
+    static ref MODEL_METADATA: Mutex<HashMap<(String, String), ModelList>> =
+        Mutex::new(HashMap::new());
+}
 
-                // Add reasoning_effort parameter
-                if let Some(effort) = &reasoning_config.reasoning_effort {
-                    body["reasoning_effort"] = serde_json::json!(effort);
-                }
-            }
-            Some(ReasoningProvider::DeepSeek) => {
-                // DeepSeek uses thinking parameter
-                body["max_tokens"] = serde_json::json!(request.max_tokens);
-                body["thinking"] = serde_json::json!({
-                    "type": if reasoning_config.enable_thinking { "enabled" } else { "disabled" }
-                });
-            }
-            Some(ReasoningProvider::Qwen) => {
-                // Qwen uses enable_thinking and thinking_budget
-                body["max_tokens"] = serde_json::json!(request.max_tokens);
-
-                if reasoning_config.enable_thinking {
-                    body["enable_thinking"] = serde_json::json!(true);
-                    if let Some(budget) = reasoning_config.thinking_budget_tokens {
-                        body["thinking_budget"] = serde_json::json!(budget);
-                    }
-                }
+/// Limit on the `/models` fetch a request may wait for before it is sent
+const MODEL_LIST_TIMEOUT_MS: u64 = 5_000;
+
+/// How long a failed `/models` fetch stands before a request tries it again, so an
+/// endpoint without the listing is not asked on every request
+const MODEL_LIST_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+enum ModelList {
+    Fetched(Vec<Value>),
+    /// The fetch failed at this time; requests go on without metadata until it is retried
+    Failed(Instant),
+}
+
+/// Add the adapter's authentication headers, then the endpoint's custom headers
+fn with_endpoint_headers(
+    adapter: &dyn ProviderAdapter,
+    req_builder: RequestBuilder,
+    endpoint: &Endpoint,
+) -> RequestBuilder {
+    let mut req_builder = adapter.apply_headers(req_builder, endpoint);
+
+    // Add custom headers
+    for (key, value) in &endpoint.headers {
+        req_builder = req_builder.header(key, value);
+    }
+
+    req_builder
+}
+
+/// Serialize the body up front so its size (dominated by any base64 media) can be reported
+fn encode_body(body: &Value) -> Result<Vec<u8>, String> {
+    serde_json::to_vec(body).map_err(|e| format!("Failed to serialize request: {}", e))
+}
+
+/// Size of the pieces a request body is handed to the connection in, so the end of a
+/// large (media) upload can be told apart from its start
+const UPLOAD_CHUNK_BYTES: usize = 16 * 1024;
+
+tokio::task_local! {
+    /// Timer of the request the current task is sending, for `TimedResolver`
+    static REQUEST_TIMER: Arc<NetworkTimer>;
+}
+
+/// Network phases of a request, in the order they finish
+#[derive(Debug, Clone, Copy)]
+enum Phase {
+    Dns,
+    Connected,
+    Uploaded,
+    Headers,
+    FirstByte,
+    TransferEnd,
+}
+
+/// Records when each network phase of one request finished. reqwest has no connector
+/// hooks, so the phases are observed from the outside: DNS by `TimedResolver`, the
+/// connection being ready by hyper starting to poll the request body (which it only
+/// does on an established connection, after any TLS handshake), and the rest by the
+/// request code as the response arrives.
+struct NetworkTimer {
+    start: Instant,
+    marks: Mutex<[Option<Instant>; 6]>,
+}
+
+impl NetworkTimer {
+    fn new(start: Instant) -> Arc<Self> {
+        Arc::new(Self {
+            start,
+            marks: Mutex::new([None; 6]),
+        })
+    }
+
+    /// Mark a phase as finished now, unless it already was
+    fn mark(&self, phase: Phase) {
+        let mut marks = self.marks.lock().unwrap_or_else(|e| e.into_inner());
+        marks[phase as usize].get_or_insert_with(Instant::now);
+    }
+
+    fn marked(&self, phase: Phase) -> Option<Instant> {
+        self.marks.lock().unwrap_or_else(|e| e.into_inner())[phase as usize]
+    }
+
+    /// Phase times since the request was sent. Connection setup is left out when the
+    /// connection was reused, since the body is then polled straight away.
+    fn timings(
+        &self,
+        connection_reused: Option<bool>,
+        first_token: Option<Instant>,
+    ) -> NetworkTimings {
+        let since_start = |mark: Option<Instant>| mark.map(|t| as_ms(t.duration_since(self.start)));
+        let new_connection = connection_reused != Some(true);
+
+        NetworkTimings {
+            dns_ms: since_start(self.marked(Phase::Dns)),
+            connect_ms: since_start(self.marked(Phase::Connected).filter(|_| new_connection)),
+            upload_ms: since_start(self.marked(Phase::Uploaded)),
+            headers_ms: since_start(self.marked(Phase::Headers)),
+            first_byte_ms: since_start(self.marked(Phase::FirstByte)),
+            first_token_ms: since_start(first_token),
+            transfer_end_ms: since_start(self.marked(Phase::TransferEnd)),
+        }
+    }
+
+    /// Run `future` (sending the request) with this timer visible to `TimedResolver`
+    async fn scope<F: std::future::Future>(self: &Arc<Self>, future: F) -> F::Output {
+        REQUEST_TIMER.scope(Arc::clone(self), future).await
+    }
+}
+
+/// DNS resolver for the shared clients that times each lookup for the request that
+/// triggered it. hyper resolves inside the task waiting for the connection, so the
+/// request's timer is found through `REQUEST_TIMER`; lookups made for anything else
+/// (model listing, connection tests) are not recorded.
+pub struct TimedResolver;
+
+impl Resolve for TimedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let timer = REQUEST_TIMER.try_with(Arc::clone).ok();
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((host, 0)).await?;
+            if let Some(timer) = timer {
+                timer.mark(Phase::Dns);
             }
-            Some(ReasoningProvider::Claude) => {
-                // Claude uses thinking parameter with budget
-                body["max_tokens"] = serde_json::json!(request.max_tokens);
-
-                if reasoning_config.enable_thinking {
-                    let mut thinking = serde_json::json!({
-                        "type": "enabled"
-                    });
-                    if let Some(budget) = reasoning_config.thinking_budget_tokens {
-                        thinking["budget_tokens"] = serde_json::json!(budget);
-                    }
-                    body["thinking"] = thinking;
-                }
+            Ok(Box::new(addrs) as Addrs)
+        })
+    }
+}
+
+/// Attach `body` so that the timer sees when the connection is ready (hyper polls the
+/// first chunk) and when the last chunk is handed to the connection. Content-Length is
+/// set explicitly so the body is not sent chunked.
+fn with_timed_body(
+    req_builder: RequestBuilder,
+    body: Vec<u8>,
+    timer: &Arc<NetworkTimer>,
+) -> RequestBuilder {
+    let length = body.len();
+    let chunks: Vec<Vec<u8>> = body
+        .chunks(UPLOAD_CHUNK_BYTES)
+        .map(<[u8]>::to_vec)
+        .collect();
+    let last = chunks.len().saturating_sub(1);
+    let timer = Arc::clone(timer);
+
+    let stream =
+        futures_util::stream::iter(chunks.into_iter().enumerate()).map(move |(index, chunk)| {
+            if index == 0 {
+                timer.mark(Phase::Connected);
             }
-            None => {
-                // Non-reasoning model
-                body["max_tokens"] = serde_json::json!(request.max_tokens);
+            if index == last {
+                timer.mark(Phase::Uploaded);
             }
-        }
-    } else {
-        // No reasoning config
-        body["max_tokens"] = serde_json::json!(request.max_tokens);
-    }
+            Ok::<_, std::io::Error>(chunk)
+        });
 
-    body
+    req_builder
+        .header(CONTENT_LENGTH, length)
+        .body(Body::wrap_stream(stream))
 }
 
-pub async fn send_llm_request(endpoint: &Endpoint, request: &LLMRequest) -> Result<LLMResponse, String> {
-    let client = Client::new();
+/// Read a whole response body, marking when its first bytes and its end arrive
+async fn read_body(mut response: Response, timer: &NetworkTimer) -> Result<String, reqwest::Error> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        timer.mark(Phase::FirstByte);
+        body.extend_from_slice(&chunk);
+    }
+    timer.mark(Phase::TransferEnd);
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
 
-    let request_body = build_request_body(endpoint, request, false);
+/// Receives streamed output as it arrives (Tauri events in the app, stdout in the CLI)
+pub trait StreamSink: Send + Sync {
+    fn on_token(&self, token: &str) -> Result<(), String>;
+    fn on_reasoning_token(&self, token: &str) -> Result<(), String>;
+}
 
-    let mut req_builder = client
-        .post(format!("{}/chat/completions", endpoint.url))
-        .header("Content-Type", "application/json")
-        .json(&request_body);
+impl StreamSink for tauri::AppHandle {
+    fn on_token(&self, token: &str) -> Result<(), String> {
+        use tauri::Manager;
 
-    // Add API key if provided
-    if let Some(api_key) = &endpoint.api_key {
-        req_builder = req_builder.header("Authorization", format!("Bearer {}", api_key));
+        // Emit token event to frontend
+        self.emit_all("token", token)
+            .map_err(|e| format!("Failed to emit token: {}", e))
     }
 
-    // Add custom headers
-    for (key, value) in &endpoint.headers {
-        req_builder = req_builder.header(key, value);
+    fn on_reasoning_token(&self, token: &str) -> Result<(), String> {
+        use tauri::Manager;
+
+        // Reasoning goes out on its own channel so the UI can render it apart from the answer
+        self.emit_all("reasoning_token", token)
+            .map_err(|e| format!("Failed to emit reasoning token: {}", e))
     }
+}
 
-    let _start = Instant::now();
-    let response = req_builder
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+/// Discards streamed output, for callers that only need the final result
+pub struct NullSink;
 
-    let status = response.status();
+impl StreamSink for NullSink {
+    fn on_token(&self, _token: &str) -> Result<(), String> {
+        Ok(())
+    }
 
-    if !status.is_success() {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Request failed with status {}: {}", status, error_text));
+    fn on_reasoning_token(&self, _token: &str) -> Result<(), String> {
+        Ok(())
     }
+}
 
-    let response_text = response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
+/// Flatten Claude thinking blocks into the reasoning text used by other providers
+pub fn reasoning_text(reasoning: Option<String>, blocks: &[ThinkingBlock]) -> Option<String> {
+    if reasoning.is_some() || blocks.is_empty() {
+        return reasoning;
+    }
 
-    let parsed: serde_json::Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+    Some(
+        blocks
+            .iter()
+            .map(|b| b.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    )
+}
 
-    let provider = detect_provider(&request.model);
+/// Pick the adapter for a request. The endpoint's `/models` listing is only fetched
+/// (once per endpoint) when the setting, API style and model name are inconclusive.
+async fn resolve_for_request(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    model: &str,
+    cancel: &CancelToken,
+) -> (&'static dyn ProviderAdapter, ProviderResolution) {
+    let metadata = if needs_metadata(endpoint, model) {
+        model_metadata(http, endpoint, model, cancel).await
+    } else {
+        None
+    };
+    resolve_adapter(endpoint, model, metadata.as_ref())
+}
 
-    // Extract reasoning content based on provider
-    let reasoning_content = match provider {
-        Some(ReasoningProvider::DeepSeek) | Some(ReasoningProvider::Qwen) => {
-            parsed["choices"][0]["message"]["reasoning_content"]
-                .as_str()
-                .map(|s| s.to_string())
-        }
+/// The model's entry in the endpoint's `/models` listing, fetching it if not cached.
+/// The fetch is cut short after `MODEL_LIST_TIMEOUT_MS` or when the request is
+/// cancelled, and a failed fetch is tried again after `MODEL_LIST_RETRY_AFTER`.
+async fn model_metadata(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    model: &str,
+    cancel: &CancelToken,
+) -> Option<Value> {
+    let key = model_list_key(endpoint);
+    let cached = match MODEL_METADATA
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&key)
+    {
+        Some(ModelList::Fetched(models)) => Some(models.clone()),
+        Some(ModelList::Failed(at)) if at.elapsed() < MODEL_LIST_RETRY_AFTER => Some(Vec::new()),
         _ => None,
     };
-
-    // Extract thinking blocks for Claude
-    let thinking_blocks = match provider {
-        Some(ReasoningProvider::Claude) => {
-            // Claude returns thinking as content blocks
-            if let Some(content_array) = parsed["choices"][0]["message"]["content"].as_array() {
-                content_array
-                    .iter()
-                    .filter_map(|block| {
-                        if block["type"] == "thinking" {
-                            Some(crate::types::ThinkingBlock {
-                                content: block["thinking"].as_str().unwrap_or("").to_string(),
-                                summary: block["summary"].as_str().map(|s| s.to_string()),
-                            })
-                        } else {
-                            None
-                        }
-                    })
-                    .collect()
-            } else {
-                Vec::new()
-            }
+    let models = match cached {
+        Some(models) => models,
+        None => {
+            let deadlines = Deadlines::new(&endpoint.timeouts, Instant::now());
+            let fetch = deadlines.run(
+                Some(MODEL_LIST_TIMEOUT_MS),
+                "the model list",
+                fetch_model_list(http, endpoint),
+            );
+            let fetched = tokio::select! {
+                fetched = fetch => fetched.and_then(|result| result),
+                // The request goes on to notice the cancellation itself
+                _ = cancel.cancelled() => return None,
+            };
+            let (models, list) = match fetched {
+                Ok(models) => (models.clone(), ModelList::Fetched(models)),
+                Err(_) => (Vec::new(), ModelList::Failed(Instant::now())),
+            };
+            MODEL_METADATA
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(key, list);
+            models
         }
-        _ => Vec::new(),
     };
 
-    let content = parsed["choices"][0]["message"]["content"]
-        .as_str()
-        .unwrap_or("")
-        .to_string();
-
-    let usage = parsed.get("usage").and_then(|u| {
-        Some(UsageMetrics {
-            prompt_tokens: u["prompt_tokens"].as_u64()? as u32,
-            completion_tokens: u["completion_tokens"].as_u64()? as u32,
-            total_tokens: u["total_tokens"].as_u64()? as u32,
-            reasoning_tokens: u.get("reasoning_tokens").and_then(|t| t.as_u64()).map(|t| t as u32),
-        })
-    });
+    models.into_iter().find(|entry| entry["id"] == model)
+}
 
-    Ok(LLMResponse {
-        content,
-        usage,
-        finish_reason: parsed["choices"][0]["finish_reason"]
-            .as_str()
-            .unwrap_or("stop")
-            .to_string(),
-        reasoning_content,
-        thinking_blocks,
-        reasoning_provider: provider,
-    })
+fn model_list_key(endpoint: &Endpoint) -> (String, String) {
+    (endpoint.id.clone(), endpoint.url.clone())
 }
 
-pub async fn send_llm_request_streaming(
+/// Response returned when a non-streaming request is cancelled before its body arrives
+fn cancelled_response(
+    adapter: &dyn ProviderAdapter,
+    resolution: &ProviderResolution,
+    start: Instant,
+) -> LLMResponse {
+    LLMResponse {
+        content: String::new(),
+        usage: None,
+        finish_reason: "cancelled".to_string(),
+        reasoning_content: None,
+        thinking_blocks: Vec::new(),
+        reasoning_provider: adapter.reasoning_provider(),
+        cancelled: true,
+        metrics: non_streaming_metrics(start.elapsed(), None),
+        tool_calls: Vec::new(),
+        validation: None,
+        provider_resolution: Some(resolution.clone()),
+        logprobs: Vec::new(),
+        logprob_stats: None,
+        warnings: Vec::new(),
+    }
+}
+
+/// A successful response, with the timer and deadlines of the attempt that got it
+struct Sent {
+    response: Response,
+    timer: Arc<NetworkTimer>,
+    deadlines: Deadlines,
+    connection_reused: Option<bool>,
+    connection: Option<ConnectionId>,
+}
+
+/// Send a generation request, retrying the failures the endpoint's retry policy allows.
+/// Every attempt gets its own timer and deadlines, so the request's metrics describe
+/// the attempt that succeeded and `retries` accounts for the rest. `None` if cancelled.
+async fn send_with_retries(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    adapter: &dyn ProviderAdapter,
+    url: &str,
+    body: &[u8],
+    cancel: &CancelToken,
+    retries: &mut RetryStats,
+) -> Result<Option<Sent>, AppError> {
+    let client = http.for_endpoint(endpoint)?;
+    let policy = &endpoint.retry;
+    let timeouts = &endpoint.timeouts;
+    let retries_error = |kind| policy.retry_errors.contains(&kind);
+
+    loop {
+        retries.attempts += 1;
+        let start = Instant::now();
+        let timer = NetworkTimer::new(start);
+        let req_builder = client.post(url).header("Content-Type", "application/json");
+        let req_builder = with_timed_body(req_builder, body.to_vec(), &timer);
+        let req_builder = with_endpoint_headers(adapter, req_builder, endpoint);
+
+        let deadlines = Deadlines::new(timeouts, start);
+        let send = timer.scope(req_builder.send());
+        let result = tokio::select! {
+            result = deadlines.run(timeouts.read_ms, "the response", send) => result,
+            _ = cancel.cancelled() => return Ok(None),
+        };
+
+        let (error, retryable, server_delay) = match result {
+            Ok(Ok(response)) if response.status().is_success() => {
+                timer.mark(Phase::Headers);
+                let connection_reused = http.connection_reused(&response);
+                let connection = connection_id(&response);
+                return Ok(Some(Sent {
+                    response,
+                    timer,
+                    deadlines,
+                    connection_reused,
+                    connection,
+                }));
+            }
+            Ok(Ok(response)) => {
+                // The connection stays open for the next attempt
+                http.connection_reused(&response);
+                let status = response.status();
+                let headers = response.headers().clone();
+                let server_delay = retry::server_delay(&headers, chrono::Utc::now());
+                let error_text = response
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| "Unknown error".to_string());
+                (
+                    AppError::from_response("Request failed", status, &headers, &error_text),
+                    policy.retry_statuses.contains(&status.as_u16()),
+                    server_delay,
+                )
+            }
+            Ok(Err(e)) => {
+                let retryable = (e.is_connect() && retries_error(RetryableError::Connect))
+                    || (e.is_timeout() && retries_error(RetryableError::Timeout));
+                (send_error(e, timeouts), retryable, None)
+            }
+            Err(timeout) => (timeout, retries_error(RetryableError::Timeout), None),
+        };
+
+        let delay = retryable
+            .then(|| retry::retry_delay(policy, retries.attempts, server_delay))
+            .flatten();
+        retries.failed_attempts_ms += as_ms(start.elapsed());
+        let Some(delay) = delay else {
+            let mut error = error;
+            retries.errors.push(error.message.clone());
+            if retries.attempts > 1 {
+                error.message = format!("{} (after {} attempts)", error.message, retries.attempts);
+            }
+            return Err(error);
+        };
+
+        retries.errors.push(error.message);
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = cancel.cancelled() => return Ok(None),
+        }
+        retries.wait_ms += as_ms(delay);
+    }
+}
+
+pub async fn send_llm_request(
+    http: &HttpClient,
     endpoint: &Endpoint,
     request: &LLMRequest,
-    app_handle: tauri::AppHandle,
-) -> Result<String, String> {
-    use futures::StreamExt;
+    cancel: &CancelToken,
+) -> Result<LLMResponse, AppError> {
+    let mut retries = RetryStats::default();
+    let result = run_request(http, endpoint, request, cancel, &mut retries).await;
+    with_retries(result, retries, |response| &mut response.metrics)
+}
 
-    let client = Client::new();
+/// Attach the attempts made to a request's outcome, so a request that failed after
+/// retrying still reports them. Left alone if it failed before anything was sent.
+fn with_retries<T>(
+    result: Result<T, AppError>,
+    retries: RetryStats,
+    metrics: impl FnOnce(&mut T) -> &mut PerformanceMetrics,
+) -> Result<T, AppError> {
+    if retries.attempts == 0 {
+        return result;
+    }
 
-    let request_body = build_request_body(endpoint, request, true);
+    match result {
+        Ok(mut outcome) => {
+            metrics(&mut outcome).retries = Some(retries);
+            Ok(outcome)
+        }
+        Err(mut error) => {
+            error.retries = Some(Box::new(retries));
+            Err(error)
+        }
+    }
+}
+
+async fn run_request(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    request: &LLMRequest,
+    cancel: &CancelToken,
+    retries: &mut RetryStats,
+) -> Result<LLMResponse, AppError> {
+    // Local media is encoded before the clock starts
+    let request = inline_local_media(request)?;
+    let request = request.as_ref();
+    let (adapter, resolution) = resolve_for_request(http, endpoint, &request.model, cancel).await;
+    let url = adapter.generation_url(endpoint, request, false);
+    let body = adapter
+        .build_body(request, false)
+        .map_err(|e| AppError::new(ErrorKind::InvalidRequest, e))?;
+    let request_body = encode_body(&body)?;
+    let request_bytes = request_body.len();
 
-    let mut req_builder = client
-        .post(format!("{}/chat/completions", endpoint.url))
-        .header("Content-Type", "application/json")
-        .json(&request_body);
+    let start = Instant::now();
+    let sent = send_with_retries(
+        http,
+        endpoint,
+        adapter,
+        &url,
+        &request_body,
+        cancel,
+        retries,
+    )
+    .await?;
+    let Some(Sent {
+        response,
+        timer,
+        deadlines,
+        connection_reused,
+        connection,
+    }) = sent
+    else {
+        return Ok(cancelled_response(adapter, &resolution, start));
+    };
 
-    // Add API key if provided
-    if let Some(api_key) = &endpoint.api_key {
-        req_builder = req_builder.header("Authorization", format!("Bearer {}", api_key));
+    let timeouts = &endpoint.timeouts;
+    let read = read_body(response, &timer);
+    let response_text = tokio::select! {
+        text = deadlines.run(timeouts.read_ms, "the response body", read) => {
+            text?.map_err(|e| {
+                AppError::new(ErrorKind::Connection, format!("Failed to read response: {}", e))
+            })?
+        }
+        _ = cancel.cancelled() => return Ok(cancelled_response(adapter, &resolution, start)),
+    };
+    // Latency of the attempt that succeeded; earlier ones are in `retries`
+    let latency = timer.start.elapsed();
+    if let Some(connection) = connection {
+        http.connection_released(connection);
     }
 
-    // Add custom headers
-    for (key, value) in &endpoint.headers {
-        req_builder = req_builder.header(key, value);
+    let parsed: serde_json::Value = serde_json::from_str(&response_text).map_err(|e| {
+        AppError::new(
+            ErrorKind::MalformedResponse,
+            format!("Failed to parse {} response: {}", adapter.name(), e),
+        )
+    })?;
+
+    let mut llm_response = adapter.parse_response(&parsed)?;
+    llm_response.metrics = non_streaming_metrics(
+        latency,
+        llm_response.usage.as_ref().map(|u| u.completion_tokens),
+    );
+    llm_response.metrics.request_bytes = request_bytes;
+    llm_response.metrics.connection_reused = connection_reused;
+    // The whole answer arrives with the body, so its first token is at the end of it
+    llm_response.metrics.network =
+        Some(timer.timings(connection_reused, timer.marked(Phase::TransferEnd)));
+    llm_response.provider_resolution = Some(resolution);
+    llm_response.logprob_stats = logprob_stats(&llm_response.logprobs);
+    llm_response.validation = request
+        .response_format
+        .as_ref()
+        .and_then(|format| validate_output(format, &llm_response.content));
+
+    Ok(llm_response)
+}
+
+/// Merge a fragment into the calls reassembled so far, keyed by stream index
+fn apply_tool_call_delta(calls: &mut Vec<(usize, ToolCall)>, delta: &ToolCallDelta) {
+    let position = match calls.iter().position(|(index, _)| *index == delta.index) {
+        Some(position) => position,
+        None => {
+            calls.push((
+                delta.index,
+                ToolCall {
+                    kind: "function".to_string(),
+                    ..Default::default()
+                },
+            ));
+            calls.len() - 1
+        }
+    };
+
+    let call = &mut calls[position].1;
+    if let Some(id) = &delta.id {
+        call.id = id.clone();
     }
+    if let Some(name) = &delta.name {
+        if call.function.name.is_empty() {
+            call.function.name = name.clone();
+        }
+    }
+    call.function.arguments.push_str(&delta.arguments);
+}
 
-    let response = req_builder
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+/// Combine usage reported across stream events. Anthropic splits prompt-side
+/// counts (`message_start`) from the running output count (`message_delta`).
+fn merge_stream_usage(previous: Option<UsageMetrics>, update: UsageMetrics) -> UsageMetrics {
+    let Some(previous) = previous else {
+        return update;
+    };
 
-    if !response.status().is_success() {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Request failed: {}", error_text));
+    let prompt_tokens = if update.prompt_tokens > 0 {
+        update.prompt_tokens
+    } else {
+        previous.prompt_tokens
+    };
+
+    UsageMetrics {
+        prompt_tokens,
+        completion_tokens: update.completion_tokens,
+        total_tokens: prompt_tokens + update.completion_tokens,
+        reasoning_tokens: update.reasoning_tokens.or(previous.reasoning_tokens),
+        cache_creation_tokens: update.cache_creation_tokens.or(previous.cache_creation_tokens),
+        cache_read_tokens: update.cache_read_tokens.or(previous.cache_read_tokens),
     }
+}
+
+/// Block that streamed thinking or summary text for `index` belongs to; a new index
+/// starts a new block
+fn thinking_block<'a>(
+    blocks: &'a mut Vec<ThinkingBlock>,
+    current_index: &mut Option<u64>,
+    index: u64,
+) -> &'a mut ThinkingBlock {
+    if *current_index != Some(index) {
+        *current_index = Some(index);
+        blocks.push(ThinkingBlock {
+            content: String::new(),
+            summary: None,
+        });
+    }
+    blocks.last_mut().unwrap()
+}
+
+pub async fn send_llm_request_streaming(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    request: &LLMRequest,
+    request_id: &str,
+    cancel: &CancelToken,
+    sink: &dyn StreamSink,
+) -> Result<StreamingResult, AppError> {
+    let mut retries = RetryStats::default();
+    let result = run_streaming_request(
+        http,
+        endpoint,
+        request,
+        request_id,
+        cancel,
+        sink,
+        &mut retries,
+    )
+    .await;
+    with_retries(result, retries, |streamed| &mut streamed.metrics)
+}
+
+async fn run_streaming_request(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+    request: &LLMRequest,
+    request_id: &str,
+    cancel: &CancelToken,
+    sink: &dyn StreamSink,
+    retries: &mut RetryStats,
+) -> Result<StreamingResult, AppError> {
+    // Local media is encoded before the clock starts
+    let request = inline_local_media(request)?;
+    let request = request.as_ref();
+    let (adapter, resolution) = resolve_for_request(http, endpoint, &request.model, cancel).await;
+    let url = adapter.generation_url(endpoint, request, true);
+    let body = adapter
+        .build_body(request, true)
+        .map_err(|e| AppError::new(ErrorKind::InvalidRequest, e))?;
+    let request_body = encode_body(&body)?;
+    let request_bytes = request_body.len();
+
+    let start = Instant::now();
+    let sent = send_with_retries(
+        http,
+        endpoint,
+        adapter,
+        &url,
+        &request_body,
+        cancel,
+        retries,
+    )
+    .await?;
+
+    // Timed from the attempt that got the response; earlier ones are in `retries`
+    let mut collector =
+        MetricsCollector::starting_at(sent.as_ref().map_or(start, |sent| sent.timer.start));
+    collector.set_request_bytes(request_bytes);
+
+    let Some(Sent {
+        response,
+        timer,
+        deadlines,
+        connection_reused,
+        connection,
+    }) = sent
+    else {
+        return Ok(StreamingResult {
+            request_id: request_id.to_string(),
+            content: String::new(),
+            reasoning_content: None,
+            thinking_blocks: Vec::new(),
+            usage: None,
+            metrics: collector.finalize(None),
+            cancelled: true,
+            finish_reason: None,
+            tool_calls: Vec::new(),
+            validation: None,
+            provider_resolution: Some(resolution),
+            logprobs: Vec::new(),
+            logprob_stats: None,
+            warnings: Vec::new(),
+        });
+    };
+    collector.set_connection_reused(connection_reused);
+    let timeouts = &endpoint.timeouts;
 
     let mut stream = response.bytes_stream();
     let mut full_content = String::new();
-    let mut buffer = Vec::new();
+    let mut reasoning_content = String::new();
+    let mut thinking_blocks: Vec<ThinkingBlock> = Vec::new();
+    let mut thinking_index: Option<u64> = None;
+    let mut usage: Option<UsageMetrics> = None;
+    let mut finish_reason: Option<String> = None;
+    let mut tool_calls: Vec<(usize, ToolCall)> = Vec::new();
+    let mut logprobs: Vec<TokenLogprob> = Vec::new();
+    let mut decoder = SseDecoder::new();
+    let mut cancelled = false;
+    let mut done = false;
+
+    while !done {
+        let item = tokio::select! {
+            item = deadlines.run(timeouts.idle_stream_ms, "the next stream chunk", stream.next()) => item?,
+            _ = cancel.cancelled() => {
+                cancelled = true;
+                break;
+            }
+        };
+        let events = match item {
+            Some(item) => {
+                timer.mark(Phase::FirstByte);
+                decoder.push(&item.map_err(|e| {
+                    AppError::new(ErrorKind::Connection, format!("Stream error: {}", e))
+                })?)
+            }
+            // End of body: deliver an event left without its terminating blank line
+            None => {
+                done = true;
+                decoder.finish().into_iter().collect()
+            }
+        };
 
-    use tauri::Manager;
+        for event in events {
+            // OpenAI-style end of stream; stop reading rather than wait for the close
+            if event.data.trim() == "[DONE]" {
+                done = true;
+                break;
+            }
 
-    while let Some(item) = stream.next().await {
-        let chunk = item.map_err(|e| format!("Stream error: {}", e))?;
-        buffer.extend_from_slice(&chunk);
+            if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&event.data) {
+                if let Some(error) = AppError::from_stream_event(&parsed) {
+                    return Err(error);
+                }
 
-        // Process complete SSE lines
-        while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
-            let line = buffer.drain(..=pos).collect::<Vec<u8>>();
-            buffer.drain(..1);
+                let delta = adapter.parse_stream_event(&parsed)?;
 
-            let line_str = String::from_utf8_lossy(&line);
+                if let Some(chunk_usage) = delta.usage {
+                    usage = Some(merge_stream_usage(usage, chunk_usage));
+                }
 
-            if line_str.starts_with("data: ") {
-                let data = &line_str[6..];
+                if delta.finish_reason.is_some() {
+                    finish_reason = delta.finish_reason;
+                }
 
-                if data.trim() == "[DONE]" {
-                    break;
+                if let Some(content) = delta.content {
+                    if !content.is_empty() {
+                        collector.record_token(&content);
+
+                        sink.on_token(&content)?;
+
+                        full_content.push_str(&content);
+                    }
                 }
 
-                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(data) {
-                    if let Some(content) =
-                        parsed["choices"][0]["delta"]["content"].as_str()
-                    {
-                        if !content.is_empty() {
-                            // Emit token event to frontend
-                            app_handle
-                                .emit_all("token", content)
-                                .map_err(|e| format!("Failed to emit token: {}", e))?;
-
-                            full_content.push_str(content);
-                        }
+                if let Some(reasoning) = delta.reasoning {
+                    if !reasoning.is_empty() {
+                        collector.record_reasoning_token(&reasoning);
+
+                        sink.on_reasoning_token(&reasoning)?;
+
+                        reasoning_content.push_str(&reasoning);
                     }
                 }
+
+                logprobs.extend(delta.logprobs);
+
+                for call in &delta.tool_calls {
+                    collector.record_tool_call_fragment(call.index, &call.arguments);
+                    apply_tool_call_delta(&mut tool_calls, call);
+                }
+
+                if let Some((index, thinking)) = delta.thinking {
+                    if !thinking.is_empty() {
+                        collector.record_reasoning_token(&thinking);
+
+                        sink.on_reasoning_token(&thinking)?;
+                    }
+
+                    thinking_block(&mut thinking_blocks, &mut thinking_index, index)
+                        .content
+                        .push_str(&thinking);
+                }
+
+                if let Some((index, summary)) = delta.summary {
+                    if !summary.is_empty() {
+                        collector.record_reasoning_token(&summary);
+
+                        sink.on_reasoning_token(&summary)?;
+                    }
+
+                    thinking_block(&mut thinking_blocks, &mut thinking_index, index)
+                        .summary
+                        .get_or_insert_with(String::new)
+                        .push_str(&summary);
+                }
             }
         }
     }
 
-    Ok(full_content)
-}
+    if !cancelled {
+        collector.complete_tool_calls();
+        timer.mark(Phase::TransferEnd);
+        if let Some(connection) = connection {
+            http.connection_released(connection);
+        }
+    }
+    let mut metrics = collector.finalize(usage.as_ref());
+    metrics.network = Some(timer.timings(connection_reused, collector.first_token_time()));
 
-/// Get available models from the endpoint's /models API
-pub async fn get_available_models(endpoint: &Endpoint) -> Result<Vec<String>, String> {
-    let client = Client::new();
+    // A cancelled stream is partial, so there is nothing meaningful to validate
+    let validation = match &request.response_format {
+        Some(format) if !cancelled => validate_output(format, &full_content),
+        _ => None,
+    };
 
-    let mut req_builder = client
+    Ok(StreamingResult {
+        request_id: request_id.to_string(),
+        content: full_content,
+        reasoning_content: (!reasoning_content.is_empty()).then_some(reasoning_content),
+        thinking_blocks,
+        metrics,
+        usage,
+        cancelled,
+        finish_reason,
+        tool_calls: tool_calls.into_iter().map(|(_, call)| call).collect(),
+        validation,
+        provider_resolution: Some(resolution),
+        logprob_stats: logprob_stats(&logprobs),
+        logprobs,
+        warnings: Vec::new(),
+    })
+}
+
+/// Fetch the raw `/models` entries in the adapter's format, each with an `id`
+async fn fetch_model_list(http: &HttpClient, endpoint: &Endpoint) -> Result<Vec<Value>, AppError> {
+    let req_builder = http
+        .for_endpoint(endpoint)?
         .get(format!("{}/models", endpoint.url))
         .header("Content-Type", "application/json");
-
-    // Add API key if provided
-    if let Some(api_key) = &endpoint.api_key {
-        req_builder = req_builder.header("Authorization", format!("Bearer {}", api_key));
-    }
-
-    // Add custom headers
-    for (key, value) in &endpoint.headers {
-        req_builder = req_builder.header(key, value);
-    }
+    let (adapter, _) = resolve_adapter(endpoint, &endpoint.model, None);
+    let req_builder = with_endpoint_headers(adapter, req_builder, endpoint);
 
     let response = req_builder
         .send()
         .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+        .map_err(|e| send_error(e, &endpoint.timeouts))?;
+    // Remember the connection, so a request that goes on to reuse it is not reported
+    // as having opened it
+    http.connection_reused(&response);
 
     let status = response.status();
 
     if !status.is_success() {
+        let headers = response.headers().clone();
         let error_text = response
             .text()
             .await
             .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Failed to fetch models with status {}: {}", status, error_text));
+        return Err(AppError::from_response(
+            "Failed to fetch models",
+            status,
+            &headers,
+            &error_text,
+        ));
     }
 
-    let response_text = response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
+    let response_text = response.text().await.map_err(|e| {
+        AppError::new(
+            ErrorKind::Connection,
+            format!("Failed to read response: {}", e),
+        )
+    })?;
+
+    let parsed: Value = serde_json::from_str(&response_text).map_err(|e| {
+        AppError::new(
+            ErrorKind::MalformedResponse,
+            format!("Failed to parse response: {}", e),
+        )
+    })?;
+
+    adapter.parse_model_list(&parsed).ok_or_else(|| {
+        AppError::new(
+            ErrorKind::MalformedResponse,
+            "Invalid response format: model list not found",
+        )
+    })
+}
 
-    let parsed: Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+/// Get available models from the endpoint's /models API. The entries are cached
+/// for provider detection on later requests.
+pub async fn get_available_models(
+    http: &HttpClient,
+    endpoint: &Endpoint,
+) -> Result<Vec<String>, AppError> {
+    let entries = fetch_model_list(http, endpoint).await?;
 
-    // Parse models from OpenAI-compatible format
-    let models = parsed["data"]
-        .as_array()
-        .ok_or_else(|| "Invalid response format: 'data' field not found".to_string())?
+    let models = entries
         .iter()
         .filter_map(|model| model["id"].as_str().map(|s| s.to_string()))
         .collect();
+    MODEL_METADATA
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(model_list_key(endpoint), ModelList::Fetched(entries));
 
     Ok(models)
 }
 
 /// Test endpoint validity by making a lightweight request
-pub async fn test_endpoint(endpoint: &Endpoint) -> Result<String, String> {
-    let client = Client::new();
+pub async fn test_endpoint(http: &HttpClient, endpoint: &Endpoint) -> Result<String, AppError> {
+    let client = http.for_endpoint(endpoint)?;
 
     // Try to fetch models as a lightweight test
-    let mut req_builder = client
+    let req_builder = client
         .get(format!("{}/models", endpoint.url))
         .header("Content-Type", "application/json");
-
-    // Add API key if provided
-    if let Some(api_key) = &endpoint.api_key {
-        req_builder = req_builder.header("Authorization", format!("Bearer {}", api_key));
-    }
-
-    // Add custom headers
-    for (key, value) in &endpoint.headers {
-        req_builder = req_builder.header(key, value);
-    }
+    let (adapter, _) = resolve_adapter(endpoint, &endpoint.model, None);
+    let req_builder = with_endpoint_headers(adapter, req_builder, endpoint);
 
     let start = Instant::now();
-    let response = req_builder
-        .send()
-        .await
-        .map_err(|e| format!("Connection failed: {}", e))?;
+    let response = req_builder.send().await.map_err(|e| {
+        let mut error = send_error(e, &endpoint.timeouts);
+        error.message = format!("Connection failed: {}", error.message);
+        error
+    })?;
+    // As for the model list, the next request on this connection has no setup
+    http.connection_reused(&response);
 
     let elapsed = start.elapsed().as_millis();
     let status = response.status();
 
     if !status.is_success() {
+        let headers = response.headers().clone();
         let error_text = response
             .text()
             .await
             .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Endpoint returned error {}: {}", status, error_text));
+        return Err(AppError::from_response(
+            "Endpoint returned an error",
+            status,
+            &headers,
+            &error_text,
+        ));
     }
 
     Ok(format!("Connection successful! Response time: {}ms", elapsed))
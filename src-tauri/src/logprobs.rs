@@ -0,0 +1,66 @@
+use crate::types::{LogprobStats, LowConfidenceToken, TokenLogprob};
+
+/// Number of least likely tokens reported in `LogprobStats::lowest_tokens`
+const LOWEST_TOKENS: usize = 5;
+
+/// Mean logprob, perplexity and least likely tokens; `None` when no logprobs were
+/// returned
+pub fn logprob_stats(logprobs: &[TokenLogprob]) -> Option<LogprobStats> {
+    if logprobs.is_empty() {
+        return None;
+    }
+
+    let mean_logprob = logprobs.iter().map(|t| t.logprob).sum::<f64>() / logprobs.len() as f64;
+
+    let mut lowest_tokens: Vec<LowConfidenceToken> = logprobs
+        .iter()
+        .enumerate()
+        .map(|(index, t)| LowConfidenceToken {
+            index,
+            token: t.token.clone(),
+            logprob: t.logprob,
+        })
+        .collect();
+    lowest_tokens.sort_by(|a, b| a.logprob.total_cmp(&b.logprob));
+    lowest_tokens.truncate(LOWEST_TOKENS);
+
+    Some(LogprobStats {
+        token_count: logprobs.len(),
+        mean_logprob,
+        perplexity: (-mean_logprob).exp(),
+        lowest_tokens,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(token: &str, logprob: f64) -> TokenLogprob {
+        TokenLogprob {
+            token: token.to_string(),
+            logprob,
+            top_logprobs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_logprob_stats() {
+        assert!(logprob_stats(&[]).is_none());
+
+        let logprobs: Vec<TokenLogprob> = [-0.5, -0.1, -2.0, -0.2, -0.3, -1.0, -0.0]
+            .iter()
+            .enumerate()
+            .map(|(i, lp)| token(&format!("t{}", i), *lp))
+            .collect();
+        let stats = logprob_stats(&logprobs).unwrap();
+
+        assert_eq!(stats.token_count, 7);
+        assert!((stats.mean_logprob - (-4.1 / 7.0)).abs() < 1e-9);
+        assert!((stats.perplexity - (4.1f64 / 7.0).exp()).abs() < 1e-9);
+
+        let lowest: Vec<usize> = stats.lowest_tokens.iter().map(|t| t.index).collect();
+        assert_eq!(lowest, vec![2, 5, 0, 4, 3]);
+        assert_eq!(stats.lowest_tokens[0].token, "t2");
+    }
+}
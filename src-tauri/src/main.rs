@@ -1,24 +1,59 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod adapters;
+mod batch;
+mod bench;
+mod cancel;
+mod cli;
+mod client;
 mod commands;
+mod compare;
+mod diff;
+mod error;
+mod history;
 mod http;
+mod logprobs;
+mod media;
 mod metrics;
 mod provider;
+mod retry;
+mod schema;
+mod sessions;
+mod sse;
 mod types;
 
+use cancel::RequestRegistry;
+use client::HttpClient;
 use commands::*;
 
 fn main() {
+    // A CLI subcommand runs headless instead of opening the window
+    if let Some(exit_code) = cli::run_from_args() {
+        std::process::exit(exit_code);
+    }
+
     tauri::Builder::default()
+        .manage(RequestRegistry::default())
+        .manage(HttpClient::default())
         .invoke_handler(tauri::generate_handler![
             send_request,
             send_streaming_request,
+            cancel_request,
+            run_benchmark,
+            run_batch,
+            compare_request,
             get_saved_endpoints,
             save_endpoint,
             delete_endpoint,
             get_request_history,
             clear_history,
+            create_session,
+            get_session,
+            list_sessions,
+            append_to_session,
+            fork_session,
+            delete_session,
             fetch_models,
             test_connection
         ])
@@ -0,0 +1,206 @@
+use crate::types::{ContentPart, ImageUrl, InputAudio, LLMRequest, MessageContent};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+
+fn image_mime_type(path: &Path) -> Result<&'static str, String> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("png") => Ok("image/png"),
+        Some("jpg") | Some("jpeg") => Ok("image/jpeg"),
+        Some("gif") => Ok("image/gif"),
+        Some("webp") => Ok("image/webp"),
+        _ => Err(format!(
+            "Unsupported image type for {} (expected png, jpeg, gif or webp)",
+            path.display()
+        )),
+    }
+}
+
+fn audio_format(path: &Path) -> Result<String, String> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some(format @ ("wav" | "mp3")) => Ok(format.to_string()),
+        _ => Err(format!(
+            "Unsupported audio type for {} (expected wav or mp3)",
+            path.display()
+        )),
+    }
+}
+
+fn read_base64(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(STANDARD.encode(bytes))
+}
+
+/// Path of an image reference that is neither a remote nor a data URL
+fn local_image_path(url: &str) -> Option<&Path> {
+    if url.starts_with("http://") || url.starts_with("https://") || url.starts_with("data:") {
+        return None;
+    }
+    Some(Path::new(url.strip_prefix("file://").unwrap_or(url)))
+}
+
+/// Encode a local image as a `data:<mime>;base64,...` URL
+pub fn image_data_url(path: &Path) -> Result<String, String> {
+    let mime_type = image_mime_type(path)?;
+    Ok(format!("data:{};base64,{}", mime_type, read_base64(path)?))
+}
+
+/// Image part for a URL or local file; local files are encoded immediately
+pub fn image_part(reference: &str) -> Result<ContentPart, String> {
+    let url = match local_image_path(reference) {
+        Some(path) => image_data_url(path)?,
+        None => reference.to_string(),
+    };
+    Ok(ContentPart::ImageUrl {
+        image_url: ImageUrl { url, detail: None },
+    })
+}
+
+/// Audio part for a local wav or mp3 file
+pub fn audio_part(path: &Path) -> Result<ContentPart, String> {
+    Ok(ContentPart::InputAudio {
+        input_audio: InputAudio {
+            format: audio_format(path)?,
+            data: read_base64(path)?,
+        },
+    })
+}
+
+fn has_local_media(part: &ContentPart) -> bool {
+    match part {
+        ContentPart::Text { .. } => false,
+        ContentPart::ImageUrl { image_url } => local_image_path(&image_url.url).is_some(),
+        ContentPart::InputAudio { input_audio } => input_audio.data.starts_with("file://"),
+    }
+}
+
+/// Replace local image paths and `file://` audio references with base64 data so the
+/// request can be sent as is. Borrows `request` unchanged when it has none, which lets
+/// callers encode once up front and skip the work on every send.
+pub fn inline_local_media(request: &LLMRequest) -> Result<Cow<'_, LLMRequest>, String> {
+    let needs_inlining = request
+        .messages
+        .iter()
+        .any(|message| match &message.content {
+            MessageContent::Parts(parts) => parts.iter().any(has_local_media),
+            MessageContent::Text(_) => false,
+        });
+    if !needs_inlining {
+        return Ok(Cow::Borrowed(request));
+    }
+
+    let mut request = request.clone();
+    for message in &mut request.messages {
+        let MessageContent::Parts(parts) = &mut message.content else {
+            continue;
+        };
+
+        for part in parts.iter_mut() {
+            match part {
+                ContentPart::ImageUrl { image_url } => {
+                    if let Some(path) = local_image_path(&image_url.url) {
+                        image_url.url = image_data_url(path)?;
+                    }
+                }
+                ContentPart::InputAudio { input_audio } => {
+                    if let Some(path) = input_audio.data.strip_prefix("file://") {
+                        let path = Path::new(path);
+                        input_audio.format = audio_format(path)?;
+                        input_audio.data = read_base64(path)?;
+                    }
+                }
+                ContentPart::Text { .. } => {}
+            }
+        }
+    }
+
+    Ok(Cow::Owned(request))
+}
+
+/// Split a base64 data URL into its media type and payload
+pub fn parse_data_url(url: &str) -> Option<(&str, &str)> {
+    let rest = url.strip_prefix("data:")?;
+    let (media_type, data) = rest.split_once(',')?;
+    Some((media_type.strip_suffix(";base64")?, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Message;
+
+    fn request_with(parts: Vec<ContentPart>) -> LLMRequest {
+        serde_json::from_value(serde_json::json!({
+            "model": "m",
+            "messages": [{"role": "user", "content": parts}],
+            "temperature": 0.0,
+            "max_tokens": 16,
+            "stream": false,
+            "reasoningConfig": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_content_formats() {
+        let plain: Message = serde_json::from_str(r#"{"role":"user","content":"hi"}"#).unwrap();
+        assert!(matches!(plain.content, MessageContent::Text(_)));
+
+        let parts: Message = serde_json::from_str(
+            r#"{"role":"user","content":[{"type":"text","text":"what is this?"},{"type":"image_url","image_url":{"url":"https://x/y.png"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(parts.content.text(), "what is this?");
+        let round_trip = serde_json::to_value(&parts).unwrap();
+        assert_eq!(round_trip["content"][1]["type"], "image_url");
+    }
+
+    #[test]
+    fn test_inline_local_image() {
+        let path = std::env::temp_dir().join("modelcurl-media-test.png");
+        fs::write(&path, b"not really a png").unwrap();
+
+        let request = request_with(vec![
+            ContentPart::Text {
+                text: "describe".to_string(),
+            },
+            ContentPart::ImageUrl {
+                image_url: ImageUrl {
+                    url: format!("file://{}", path.display()),
+                    detail: None,
+                },
+            },
+        ]);
+
+        let inlined = inline_local_media(&request).unwrap();
+        let MessageContent::Parts(parts) = &inlined.messages[0].content else {
+            panic!("expected parts");
+        };
+        let ContentPart::ImageUrl { image_url } = &parts[1] else {
+            panic!("expected an image part");
+        };
+        let (media_type, data) = parse_data_url(&image_url.url).unwrap();
+        assert_eq!(media_type, "image/png");
+        assert_eq!(STANDARD.decode(data).unwrap(), b"not really a png");
+
+        // Already-inlined requests are passed through without copying
+        assert!(matches!(
+            inline_local_media(&inlined).unwrap(),
+            Cow::Borrowed(_)
+        ));
+
+        fs::remove_file(path).ok();
+    }
+}
@@ -1,24 +1,117 @@
-use crate::types::PerformanceMetrics;
+use crate::types::{AppError, LatencyStats, PerformanceMetrics, ToolCallTiming, UsageMetrics};
 use std::time::{Duration, Instant};
 
 pub struct MetricsCollector {
     start_time: Instant,
     first_token_time: Option<Instant>,
+    first_reasoning_time: Option<Instant>,
+    first_answer_time: Option<Instant>,
     token_timestamps: Vec<Instant>,
-    token_count: usize,
+    reasoning_chunks: usize,
+    tool_calls: Vec<ToolCallTimes>,
+    request_bytes: usize,
+    connection_reused: Option<bool>,
+}
+
+struct ToolCallTimes {
+    /// Index of the call in the stream (the content block index for Anthropic)
+    stream_index: usize,
+    first_fragment: Instant,
+    last_fragment: Instant,
+    complete: bool,
 }
 
 impl MetricsCollector {
     pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+
+    /// Collector for a request sent at `start_time`
+    pub fn starting_at(start_time: Instant) -> Self {
         Self {
-            start_time: Instant::now(),
+            start_time,
             first_token_time: None,
+            first_reasoning_time: None,
+            first_answer_time: None,
             token_timestamps: Vec::new(),
-            token_count: 0,
+            reasoning_chunks: 0,
+            tool_calls: Vec::new(),
+            request_bytes: 0,
+            connection_reused: None,
         }
     }
 
+    /// When the first output token arrived, if one has
+    pub fn first_token_time(&self) -> Option<Instant> {
+        self.first_token_time
+    }
+
+    /// Size of the request body, reported alongside the timings
+    pub fn set_request_bytes(&mut self, bytes: usize) {
+        self.request_bytes = bytes;
+    }
+
+    /// Whether the response came over a pooled connection
+    pub fn set_connection_reused(&mut self, reused: Option<bool>) {
+        self.connection_reused = reused;
+    }
+
+    /// Record an answer (content) token
     pub fn record_token(&mut self, token: &str) {
+        let now = self.record_output(token);
+
+        if self.first_answer_time.is_none() && !token.trim().is_empty() {
+            self.first_answer_time = Some(now);
+        }
+    }
+
+    /// Record a reasoning/thinking token, streamed before or alongside the answer
+    pub fn record_reasoning_token(&mut self, token: &str) {
+        let now = self.record_output(token);
+        self.reasoning_chunks += 1;
+
+        if self.first_reasoning_time.is_none() && !token.trim().is_empty() {
+            self.first_reasoning_time = Some(now);
+        }
+    }
+
+    /// Record a tool call header or argument fragment. Argument text counts as output
+    /// for TTFT/TPOT, since a call-only reply streams nothing else.
+    pub fn record_tool_call_fragment(&mut self, stream_index: usize, arguments: &str) {
+        let now = if arguments.is_empty() {
+            Instant::now()
+        } else {
+            self.record_output(arguments)
+        };
+
+        if let Some(call) = self
+            .tool_calls
+            .iter_mut()
+            .find(|c| c.stream_index == stream_index)
+        {
+            call.last_fragment = now;
+            return;
+        }
+
+        // Calls stream one after another, so a new call finishes the previous ones
+        self.complete_tool_calls();
+        self.tool_calls.push(ToolCallTimes {
+            stream_index,
+            first_fragment: now,
+            last_fragment: now,
+            complete: false,
+        });
+    }
+
+    /// Mark every tool call seen so far as finished
+    pub fn complete_tool_calls(&mut self) {
+        for call in &mut self.tool_calls {
+            call.complete = true;
+        }
+    }
+
+    /// Common bookkeeping for any output token; TTFT counts either kind
+    fn record_output(&mut self, token: &str) -> Instant {
         let now = Instant::now();
 
         if self.first_token_time.is_none() && !token.trim().is_empty() {
@@ -26,45 +119,72 @@ impl MetricsCollector {
         }
 
         self.token_timestamps.push(now);
-        self.token_count += token.len();
+        now
     }
 
-    pub fn finalize(&self) -> PerformanceMetrics {
+    /// Build the final metrics. When the server reported usage, token counts come from
+    /// its `completion_tokens` rather than the number of SSE chunks, which can carry
+    /// several tokens each.
+    pub fn finalize(&self, usage: Option<&UsageMetrics>) -> PerformanceMetrics {
         let ttft = self
             .first_token_time
             .map(|t| t.duration_since(self.start_time))
             .unwrap_or(Duration::from_millis(0));
 
         let total_duration = self.start_time.elapsed();
+        let stream_chunks = self.token_timestamps.len();
+        let total_tokens = usage
+            .map(|u| u.completion_tokens as usize)
+            .unwrap_or(stream_chunks);
 
-        // Calculate TPOT (Time Per Output Token)
-        let tpot_samples: Vec<Duration> = self
-            .token_timestamps
-            .windows(2)
-            .map(|w| w[1].duration_since(w[0]))
-            .collect();
+        // Calculate TPOT (Time Per Output Token) over the decode span
+        let decode_span = match (self.token_timestamps.first(), self.token_timestamps.last()) {
+            (Some(first), Some(last)) => last.duration_since(*first),
+            _ => Duration::ZERO,
+        };
 
-        let avg_tpot = if !tpot_samples.is_empty() {
-            let sum: Duration = tpot_samples.iter().sum();
-            Some(sum.as_millis() as f64 / tpot_samples.len() as f64)
+        let avg_tpot = if total_tokens > 1 && stream_chunks > 1 {
+            Some(as_ms(decode_span) / (total_tokens - 1) as f64)
         } else {
             None
         };
 
-        let tokens_per_second = if total_duration.as_millis() > 0 {
-            Some(
-                (self.token_timestamps.len() as f64 / total_duration.as_secs_f64()) * 1000.0,
-            )
+        let tokens_per_second = if !total_duration.is_zero() {
+            Some(total_tokens as f64 / total_duration.as_secs_f64())
         } else {
             None
         };
 
         PerformanceMetrics {
-            ttft_ms: ttft.as_millis() as f64,
+            ttft_ms: as_ms(ttft),
             avg_tpot_ms: avg_tpot,
-            total_latency_ms: total_duration.as_millis() as f64,
-            total_tokens: self.token_timestamps.len(),
+            total_latency_ms: as_ms(total_duration),
+            total_tokens,
             tokens_per_second,
+            time_to_first_reasoning_ms: self
+                .first_reasoning_time
+                .map(|t| as_ms(t.duration_since(self.start_time))),
+            time_to_first_answer_ms: self
+                .first_answer_time
+                .map(|t| as_ms(t.duration_since(self.start_time))),
+            reasoning_chunks: self.reasoning_chunks,
+            stream_chunks,
+            tool_calls: self
+                .tool_calls
+                .iter()
+                .enumerate()
+                .map(|(index, call)| ToolCallTiming {
+                    index,
+                    started_ms: as_ms(call.first_fragment.duration_since(self.start_time)),
+                    completed_ms: call
+                        .complete
+                        .then(|| as_ms(call.last_fragment.duration_since(self.start_time))),
+                })
+                .collect(),
+            request_bytes: self.request_bytes,
+            connection_reused: self.connection_reused,
+            network: None,
+            retries: None,
         }
     }
 }
@@ -74,3 +194,134 @@ impl Default for MetricsCollector {
         Self::new()
     }
 }
+
+/// Metrics for a non-streaming request, where the whole body arrives at once
+/// so TTFT equals the total latency
+pub fn non_streaming_metrics(latency: Duration, completion_tokens: Option<u32>) -> PerformanceMetrics {
+    let total_tokens = completion_tokens.unwrap_or(0) as usize;
+
+    let tokens_per_second = if !latency.is_zero() && total_tokens > 0 {
+        Some(total_tokens as f64 / latency.as_secs_f64())
+    } else {
+        None
+    };
+
+    PerformanceMetrics {
+        ttft_ms: as_ms(latency),
+        avg_tpot_ms: None,
+        total_latency_ms: as_ms(latency),
+        total_tokens,
+        tokens_per_second,
+        time_to_first_reasoning_ms: None,
+        time_to_first_answer_ms: None,
+        reasoning_chunks: 0,
+        stream_chunks: 0,
+        tool_calls: Vec::new(),
+        request_bytes: 0,
+        connection_reused: None,
+        network: None,
+        retries: None,
+    }
+}
+
+/// Metrics for a request that failed: how long it took and any attempts it made. No
+/// token arrived, so TTFT is left at zero rather than counted as the whole latency.
+pub fn failed_request_metrics(elapsed: Duration, error: &AppError) -> PerformanceMetrics {
+    PerformanceMetrics {
+        total_latency_ms: as_ms(elapsed),
+        retries: error.retries.as_deref().cloned(),
+        ..Default::default()
+    }
+}
+
+/// Percentile of an ascending slice, linearly interpolated between closest ranks
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p / 100.0) * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// Summarize a set of latencies; `None` when there are no values
+pub fn latency_stats(values: &[f64]) -> Option<LatencyStats> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    Some(LatencyStats {
+        min: sorted[0],
+        mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+        p50: percentile(&sorted, 50.0),
+        p90: percentile(&sorted, 90.0),
+        p99: percentile(&sorted, 99.0),
+        max: sorted[sorted.len() - 1],
+    })
+}
+
+/// Convert a duration to fractional milliseconds
+pub(crate) fn as_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ErrorKind;
+
+    #[test]
+    fn test_tool_call_completion() {
+        let mut collector = MetricsCollector::new();
+        collector.record_tool_call_fragment(0, "");
+        collector.record_tool_call_fragment(0, "{\"city\":");
+        collector.record_tool_call_fragment(0, "\"Paris\"}");
+        // A second call finishes the first; the second stays open until the stream ends
+        collector.record_tool_call_fragment(3, "{}");
+
+        let metrics = collector.finalize(None);
+        assert_eq!(metrics.tool_calls.len(), 2);
+        assert_eq!(metrics.tool_calls[1].index, 1);
+        assert!(metrics.tool_calls[0].completed_ms.is_some());
+        assert!(metrics.tool_calls[1].completed_ms.is_none());
+        assert_eq!(metrics.total_tokens, 3);
+    }
+
+    #[test]
+    fn test_failed_request_has_no_ttft() {
+        let error = AppError::new(ErrorKind::Connection, "refused");
+        let metrics = failed_request_metrics(Duration::from_millis(250), &error);
+        assert_eq!(metrics.ttft_ms, 0.0);
+        assert_eq!(metrics.total_latency_ms, 250.0);
+        assert_eq!(metrics.total_tokens, 0);
+        assert!(metrics.tokens_per_second.is_none());
+    }
+
+    #[test]
+    fn test_latency_stats_empty() {
+        assert!(latency_stats(&[]).is_none());
+    }
+
+    #[test]
+    fn test_latency_stats_single_value() {
+        let stats = latency_stats(&[42.0]).unwrap();
+        assert_eq!(stats.min, 42.0);
+        assert_eq!(stats.p50, 42.0);
+        assert_eq!(stats.p99, 42.0);
+        assert_eq!(stats.max, 42.0);
+    }
+
+    #[test]
+    fn test_latency_stats_interpolates() {
+        // Unsorted input: 10, 20, ..., 100
+        let values: Vec<f64> = (1..=10).rev().map(|v| v as f64 * 10.0).collect();
+        let stats = latency_stats(&values).unwrap();
+        assert_eq!(stats.min, 10.0);
+        assert_eq!(stats.max, 100.0);
+        assert_eq!(stats.mean, 55.0);
+        assert!((stats.p50 - 55.0).abs() < 1e-9);
+        assert!((stats.p90 - 91.0).abs() < 1e-9);
+        assert!((stats.p99 - 99.1).abs() < 1e-9);
+    }
+}
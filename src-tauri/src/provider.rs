@@ -1,6 +1,113 @@
-use crate::types::ReasoningProvider;
+use crate::types::{ApiStyle, Endpoint, ProviderSetting, ProviderSource, ReasoningProvider};
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde_json::Value;
+
+/// `/models` fields naming the underlying model: vLLM's `root`/`parent`, OpenRouter's
+/// `canonical_slug`/`hugging_face_id` and Anthropic's `display_name`
+const METADATA_MODEL_FIELDS: &[&str] = &[
+    "root",
+    "parent",
+    "canonical_slug",
+    "hugging_face_id",
+    "display_name",
+];
+
+/// Resolve the provider for a request: the endpoint's explicit setting wins, then
+/// the API style, the model name, and finally the model's `/models` entry.
+/// Returns the provider, where it came from and a human-readable reason.
+pub fn resolve_provider(
+    endpoint: &Endpoint,
+    model: &str,
+    metadata: Option<&Value>,
+) -> (Option<ReasoningProvider>, ProviderSource, String) {
+    if endpoint.provider != ProviderSetting::Auto {
+        return (
+            endpoint.provider.reasoning_provider(),
+            ProviderSource::Endpoint,
+            format!("endpoint provider is set to {:?}", endpoint.provider),
+        );
+    }
+
+    match endpoint.api_style {
+        ApiStyle::AnthropicMessages => {
+            return (
+                Some(ReasoningProvider::Claude),
+                ProviderSource::ApiStyle,
+                "endpoint speaks the Anthropic Messages API".to_string(),
+            )
+        }
+        ApiStyle::GeminiGenerateContent => {
+            return (
+                Some(ReasoningProvider::Gemini),
+                ProviderSource::ApiStyle,
+                "endpoint speaks the Gemini generateContent API".to_string(),
+            )
+        }
+        ApiStyle::Completions => {
+            return (
+                None,
+                ProviderSource::ApiStyle,
+                "text completions take no reasoning parameters".to_string(),
+            )
+        }
+        // Responses models are OpenAI's, but only some of them reason
+        ApiStyle::ChatCompletions | ApiStyle::Responses => {}
+    }
+
+    if let Some(provider) = detect_provider(model) {
+        return (
+            Some(provider),
+            ProviderSource::ModelName,
+            format!("model name '{}' matches the {:?} pattern", model, provider),
+        );
+    }
+
+    if let Some((provider, reason)) = metadata.and_then(provider_from_metadata) {
+        return (Some(provider), ProviderSource::ModelMetadata, reason);
+    }
+
+    (
+        None,
+        ProviderSource::Default,
+        format!(
+            "no provider detected for '{}'; sending no reasoning parameters",
+            model
+        ),
+    )
+}
+
+/// Whether `resolve_provider` would consult `/models` metadata, so callers only
+/// fetch it when the endpoint setting, API style and model name give nothing away
+pub fn needs_metadata(endpoint: &Endpoint, model: &str) -> bool {
+    endpoint.provider == ProviderSetting::Auto
+        && endpoint.api_style == ApiStyle::ChatCompletions
+        && detect_provider(model).is_none()
+}
+
+/// Detect a provider from one entry of a `/models` response. Fields naming the
+/// underlying model are matched like model names (ignoring any `org/` prefix),
+/// then `owned_by` is mapped for the vendors that report it.
+pub fn provider_from_metadata(model: &Value) -> Option<(ReasoningProvider, String)> {
+    for field in METADATA_MODEL_FIELDS {
+        let Some(value) = model[field].as_str() else {
+            continue;
+        };
+        let name = value.rsplit('/').next().unwrap_or(value).replace(' ', "-");
+        if let Some(provider) = detect_provider(&name) {
+            return Some((provider, format!("/models reports {} '{}'", field, value)));
+        }
+    }
+
+    let owned_by = model["owned_by"].as_str()?;
+    let provider = match owned_by.to_lowercase().as_str() {
+        "deepseek" | "deepseek-ai" => ReasoningProvider::DeepSeek,
+        "qwen" | "alibaba" | "dashscope" => ReasoningProvider::Qwen,
+        "anthropic" => ReasoningProvider::Claude,
+        _ => return None,
+    };
+    Some((provider, format!("/models reports owned_by '{}'", owned_by)))
+}
 
 /// Detect reasoning provider from model name using flexible pattern matching
 pub fn detect_provider(model_name: &str) -> Option<ReasoningProvider> {
@@ -32,6 +139,12 @@ pub fn detect_provider(model_name: &str) -> Option<ReasoningProvider> {
         return Some(ReasoningProvider::Claude);
     }
 
+    // Gemini thinking models
+    // Matches: gemini-2.5-pro, gemini-2.5-flash, gemini-3-pro-preview, etc.
+    if is_gemini_model(&normalized) {
+        return Some(ReasoningProvider::Gemini);
+    }
+
     None
 }
 
@@ -56,10 +169,12 @@ fn is_deepseek_model(model: &str) -> bool {
 }
 
 fn is_qwen_model(model: &str) -> bool {
-    // Pattern: qwen, qwq with potential reasoning indicators
+    // Pattern: qwen, qwq followed by any run of tier/version/size segments
+    // (qwen-plus, qwen3-max, qwq-32b, qwen2.5-72b-instruct)
     lazy_static! {
         static ref QWEN_PATTERN: Regex =
-            Regex::new(r"^(qwen|qwq)([-_.]?(plus|max|turbo|coder|\d+\.?\d*))?$").unwrap();
+            Regex::new(r"^(qwen|qwq)([-_.]?(plus|max|turbo|coder|\d+(\.\d+)?b?))*([-_.:]|$)")
+                .unwrap();
     }
 
     QWEN_PATTERN.is_match(model)
@@ -75,6 +190,15 @@ fn is_claude_model(model: &str) -> bool {
     CLAUDE_PATTERN.is_match(model)
 }
 
+fn is_gemini_model(model: &str) -> bool {
+    // Pattern: gemini 2.5 or later (earlier versions do not think)
+    lazy_static! {
+        static ref GEMINI_PATTERN: Regex = Regex::new(r"^gemini[-_]?(2\.5|[3-9])").unwrap();
+    }
+
+    GEMINI_PATTERN.is_match(model)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -180,11 +304,85 @@ mod tests {
         );
     }
 
+    fn endpoint(provider: ProviderSetting) -> Endpoint {
+        Endpoint {
+            id: "test".to_string(),
+            name: "test".to_string(),
+            url: "http://localhost".to_string(),
+            api_key: None,
+            headers: Vec::new(),
+            model: String::new(),
+            api_style: ApiStyle::ChatCompletions,
+            provider,
+            timeouts: Default::default(),
+            retry: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_provider_order() {
+        let auto = endpoint(ProviderSetting::Auto);
+        let metadata = serde_json::json!({
+            "id": "my-r1-distill",
+            "owned_by": "vllm",
+            "root": "deepseek-ai/DeepSeek-R1-Distill-Qwen-7B"
+        });
+
+        // The name gives nothing away, so the vLLM root decides
+        assert!(needs_metadata(&auto, "my-r1-distill"));
+        let (provider, source, _) = resolve_provider(&auto, "my-r1-distill", Some(&metadata));
+        assert_eq!(provider, Some(ReasoningProvider::DeepSeek));
+        assert_eq!(source, ProviderSource::ModelMetadata);
+
+        let (provider, source, _) = resolve_provider(&auto, "my-r1-distill", None);
+        assert_eq!(provider, None);
+        assert_eq!(source, ProviderSource::Default);
+
+        // An explicit setting beats a matching name
+        let generic = endpoint(ProviderSetting::Generic);
+        assert!(!needs_metadata(&generic, "my-r1-distill"));
+        let (provider, source, _) = resolve_provider(&generic, "deepseek-r1", None);
+        assert_eq!(provider, None);
+        assert_eq!(source, ProviderSource::Endpoint);
+    }
+
+    #[test]
+    fn test_provider_from_metadata() {
+        let alias = serde_json::json!({ "id": "prod", "owned_by": "anthropic" });
+        assert_eq!(
+            provider_from_metadata(&alias).map(|(p, _)| p),
+            Some(ReasoningProvider::Claude)
+        );
+
+        let anthropic = serde_json::json!({ "id": "x", "display_name": "Claude Sonnet 4" });
+        assert_eq!(
+            provider_from_metadata(&anthropic).map(|(p, _)| p),
+            Some(ReasoningProvider::Claude)
+        );
+
+        let plain = serde_json::json!({ "id": "gpt-4o", "owned_by": "openai" });
+        assert!(provider_from_metadata(&plain).is_none());
+    }
+
+    #[test]
+    fn test_gemini_detection() {
+        assert_eq!(
+            detect_provider("gemini-2.5-flash"),
+            Some(ReasoningProvider::Gemini)
+        );
+        assert_eq!(
+            detect_provider("gemini-3-pro-preview"),
+            Some(ReasoningProvider::Gemini)
+        );
+        assert_eq!(detect_provider("gemini-1.5-pro"), None);
+    }
+
     #[test]
     fn test_non_reasoning_models() {
         assert_eq!(detect_provider("gpt-4"), None);
         assert_eq!(detect_provider("gpt-3.5-turbo"), None);
         assert_eq!(detect_provider("llama-2"), None);
         assert_eq!(detect_provider("mistral-7b"), None);
+        assert_eq!(detect_provider("qwerty"), None);
     }
 }
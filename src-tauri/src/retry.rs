@@ -0,0 +1,225 @@
+use crate::types::RetryPolicy;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+/// How long to wait before the next attempt, or `None` to give up. `attempt` is the
+/// number of attempts made so far. A wait requested by the server is honoured as is;
+/// one longer than the policy's cap fails the request rather than being cut short.
+pub fn retry_delay(
+    policy: &RetryPolicy,
+    attempt: u32,
+    server_delay: Option<Duration>,
+) -> Option<Duration> {
+    if attempt >= policy.max_attempts {
+        return None;
+    }
+
+    let max = Duration::from_millis(policy.max_backoff_ms);
+    match server_delay {
+        Some(delay) if delay > max => None,
+        Some(delay) => Some(delay),
+        None => Some(backoff(policy, attempt, random_fraction())),
+    }
+}
+
+/// Exponential backoff with "equal jitter": half of the doubled delay is fixed and a
+/// random share (`jitter`, in `[0, 1)`) of the other half is added, so the wait still
+/// grows with every retry while concurrent clients spread out.
+fn backoff(policy: &RetryPolicy, attempt: u32, jitter: f64) -> Duration {
+    let doublings = attempt.saturating_sub(1).min(31);
+    let delay = policy
+        .initial_backoff_ms
+        .saturating_mul(1 << doublings)
+        .min(policy.max_backoff_ms);
+
+    Duration::from_millis(delay / 2) + Duration::from_millis(delay - delay / 2).mul_f64(jitter)
+}
+
+/// A pseudo-random number in `[0, 1)`, seeded by std's per-process random hasher keys
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().hash_one(std::time::Instant::now());
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Wait the server asked for: `Retry-After` (seconds or an HTTP date), otherwise the
+/// longest `x-ratelimit-reset-*` among the limits that are used up. Reset headers of
+/// limits with requests left say nothing about this failure, so they are ignored.
+pub fn server_delay(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    if let Some(value) = headers.get(RETRY_AFTER).and_then(|v| v.to_str().ok()) {
+        if let Some(delay) = parse_retry_after(value, now) {
+            return Some(delay);
+        }
+    }
+
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            let limit = name.as_str().strip_prefix("x-ratelimit-reset-")?;
+            let remaining = headers.get(format!("x-ratelimit-remaining-{}", limit))?;
+            if remaining.to_str().ok()?.trim() != "0" {
+                return None;
+            }
+            parse_reset(value.to_str().ok()?)
+        })
+        .max()
+}
+
+/// `Retry-After: 120` or `Retry-After: Wed, 21 Oct 2015 07:28:00 GMT`
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(seconds).ok();
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// OpenAI-style reset times: Go durations such as `6m0s`, `1.5s` or `20ms`, or a bare
+/// number of seconds
+fn parse_reset(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(seconds).ok();
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+        let unit_end = rest[number_end..]
+            .find(|c: char| c.is_ascii_digit())
+            .map_or(rest.len(), |i| number_end + i);
+        let number: f64 = rest[..number_end].parse().ok()?;
+        let seconds = match &rest[number_end..unit_end] {
+            "h" => number * 3600.0,
+            "m" => number * 60.0,
+            "s" => number,
+            "ms" => number / 1000.0,
+            _ => return None,
+        };
+        total += Duration::try_from_secs_f64(seconds).ok()?;
+        rest = &rest[unit_end..];
+    }
+
+    (!value.is_empty()).then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 4_000,
+            ..Default::default()
+        }
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn test_backoff_doubles_with_jitter_and_cap() {
+        let policy = policy(10);
+        let ms = |attempt, jitter| backoff(&policy, attempt, jitter).as_millis();
+
+        assert_eq!(ms(1, 0.0), 250);
+        assert_eq!(ms(1, 0.999), 499);
+        assert_eq!(ms(2, 0.0), 500);
+        assert_eq!(ms(3, 0.5), 1_500);
+        // Capped at max_backoff_ms, even for absurd attempt numbers
+        assert_eq!(ms(8, 0.0), 2_000);
+        assert_eq!(ms(500, 0.0), 2_000);
+    }
+
+    #[test]
+    fn test_retry_delay_limits() {
+        // One attempt means no retries
+        assert_eq!(retry_delay(&policy(1), 1, None), None);
+        assert_eq!(retry_delay(&policy(3), 3, None), None);
+
+        let delay = retry_delay(&policy(3), 1, None).unwrap();
+        assert!(delay >= Duration::from_millis(250) && delay < Duration::from_millis(500));
+
+        // A server-requested wait replaces the backoff, unless it is over the cap
+        let asked = Duration::from_secs(3);
+        assert_eq!(retry_delay(&policy(3), 2, Some(asked)), Some(asked));
+        assert_eq!(
+            retry_delay(&policy(3), 2, Some(Duration::from_secs(60))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let now = DateTime::parse_from_rfc3339("2015-10-21T07:27:30Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let delay = |pairs| server_delay(&headers(pairs), now);
+        assert_eq!(delay(&[("retry-after", "7")]), Some(Duration::from_secs(7)));
+        assert_eq!(
+            delay(&[("retry-after", "Wed, 21 Oct 2015 07:28:00 GMT")]),
+            Some(Duration::from_secs(30))
+        );
+        // A date in the past means retry now
+        assert_eq!(
+            delay(&[("retry-after", "Wed, 21 Oct 2015 07:00:00 GMT")]),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(delay(&[("retry-after", "soon")]), None);
+    }
+
+    #[test]
+    fn test_rate_limit_reset_of_exhausted_limits() {
+        let now = Utc::now();
+        // OpenAI-style 429: the token limit is used up, the request limit is not
+        let openai = headers(&[
+            ("x-ratelimit-limit-requests", "500"),
+            ("x-ratelimit-remaining-requests", "499"),
+            ("x-ratelimit-reset-requests", "120ms"),
+            ("x-ratelimit-limit-tokens", "30000"),
+            ("x-ratelimit-remaining-tokens", "0"),
+            ("x-ratelimit-reset-tokens", "1m2.5s"),
+        ]);
+        assert_eq!(
+            server_delay(&openai, now),
+            Some(Duration::from_millis(62_500))
+        );
+
+        // Retry-After wins over the reset headers
+        let mut with_retry_after = openai.clone();
+        with_retry_after.insert(RETRY_AFTER, HeaderValue::from_static("2"));
+        assert_eq!(
+            server_delay(&with_retry_after, now),
+            Some(Duration::from_secs(2))
+        );
+
+        let nothing_exhausted = headers(&[
+            ("x-ratelimit-remaining-requests", "12"),
+            ("x-ratelimit-reset-requests", "6m0s"),
+        ]);
+        assert_eq!(server_delay(&nothing_exhausted, now), None);
+
+        assert_eq!(parse_reset("1h2m3s"), Some(Duration::from_secs(3_723)));
+        assert_eq!(parse_reset("2.5"), Some(Duration::from_millis(2_500)));
+        assert_eq!(parse_reset("6x"), None);
+        assert_eq!(parse_reset(""), None);
+    }
+}
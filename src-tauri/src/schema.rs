@@ -0,0 +1,469 @@
+use crate::metrics::as_ms;
+use crate::types::{OutputValidation, ResponseFormat, SchemaViolation};
+use regex::Regex;
+use serde_json::Value;
+use std::time::Instant;
+
+/// Nesting limit for `$ref`/subschemas, so recursive schemas cannot loop forever
+const MAX_DEPTH: usize = 64;
+
+/// Check response content against the requested format; `None` for plain text
+pub fn validate_output(format: &ResponseFormat, content: &str) -> Option<OutputValidation> {
+    let schema = match format {
+        ResponseFormat::Text => return None,
+        ResponseFormat::JsonObject => None,
+        ResponseFormat::JsonSchema { json_schema } => Some(&json_schema.schema),
+    };
+
+    let start = Instant::now();
+    let instance: Value = match serde_json::from_str(content) {
+        Ok(instance) => instance,
+        Err(e) => {
+            return Some(OutputValidation {
+                parsed: false,
+                valid: false,
+                parse_error: Some(e.to_string()),
+                errors: Vec::new(),
+                validation_ms: as_ms(start.elapsed()),
+            })
+        }
+    };
+
+    let errors = match schema {
+        Some(schema) => validate(schema, &instance),
+        None if instance.is_object() => Vec::new(),
+        None => vec![SchemaViolation {
+            path: String::new(),
+            message: format!("Expected an object, got {}", type_name(&instance)),
+        }],
+    };
+
+    Some(OutputValidation {
+        parsed: true,
+        valid: errors.is_empty(),
+        parse_error: None,
+        errors,
+        validation_ms: as_ms(start.elapsed()),
+    })
+}
+
+/// Validate `instance` against a JSON Schema. Covers the keywords structured output
+/// backends accept: types, enums, objects, arrays, string/number bounds, patterns,
+/// combinators and local `$ref`s. Unknown keywords (e.g. `format`) are ignored.
+pub fn validate(schema: &Value, instance: &Value) -> Vec<SchemaViolation> {
+    let mut validator = Validator {
+        root: schema,
+        errors: Vec::new(),
+    };
+    validator.check(schema, instance, "", 0);
+    validator.errors
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    errors: Vec<SchemaViolation>,
+}
+
+impl Validator<'_> {
+    fn fail(&mut self, path: &str, message: String) {
+        self.errors.push(SchemaViolation {
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    /// Whether `instance` matches `schema`, without recording errors
+    fn matches(&self, schema: &Value, instance: &Value, depth: usize) -> bool {
+        let mut probe = Validator {
+            root: self.root,
+            errors: Vec::new(),
+        };
+        probe.check(schema, instance, "", depth);
+        probe.errors.is_empty()
+    }
+
+    fn check(&mut self, schema: &Value, instance: &Value, path: &str, depth: usize) {
+        if depth > MAX_DEPTH {
+            self.fail(path, "Schema nesting is too deep".to_string());
+            return;
+        }
+
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                self.fail(path, "No value is allowed here".to_string());
+                return;
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match reference
+                .strip_prefix('#')
+                .and_then(|pointer| self.root.pointer(pointer))
+            {
+                Some(target) => self.check(target, instance, path, depth + 1),
+                None => self.fail(path, format!("Unresolvable $ref '{}'", reference)),
+            }
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let allowed: Vec<&str> = match expected {
+                Value::String(name) => vec![name.as_str()],
+                Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !allowed.is_empty() && !allowed.iter().any(|name| is_type(instance, name)) {
+                self.fail(
+                    path,
+                    format!(
+                        "Expected {}, got {}",
+                        allowed.join(" or "),
+                        type_name(instance)
+                    ),
+                );
+                // Further keywords would only restate the type mismatch
+                return;
+            }
+        }
+
+        if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+            if !options.contains(instance) {
+                self.fail(
+                    path,
+                    format!(
+                        "{} is not one of {}",
+                        instance,
+                        Value::Array(options.clone())
+                    ),
+                );
+            }
+        }
+        if let Some(constant) = schema.get("const") {
+            if constant != instance {
+                self.fail(path, format!("Expected {}, got {}", constant, instance));
+            }
+        }
+
+        self.check_combinators(schema, instance, path, depth);
+
+        match instance {
+            Value::Object(object) => self.check_object(schema, object, path, depth),
+            Value::Array(items) => self.check_array(schema, items, path, depth),
+            Value::String(text) => self.check_string(schema, text, path),
+            Value::Number(number) => {
+                if let Some(number) = number.as_f64() {
+                    self.check_number(schema, number, path);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_combinators(
+        &mut self,
+        schema: &serde_json::Map<String, Value>,
+        instance: &Value,
+        path: &str,
+        depth: usize,
+    ) {
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            for subschema in all {
+                self.check(subschema, instance, path, depth + 1);
+            }
+        }
+        if let Some(any) = schema.get("anyOf").and_then(Value::as_array) {
+            if !any.iter().any(|s| self.matches(s, instance, depth + 1)) {
+                self.fail(path, "Does not match any schema in anyOf".to_string());
+            }
+        }
+        if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+            let matched = one
+                .iter()
+                .filter(|s| self.matches(s, instance, depth + 1))
+                .count();
+            if matched != 1 {
+                self.fail(
+                    path,
+                    format!("Matches {} schemas in oneOf; expected exactly 1", matched),
+                );
+            }
+        }
+        if let Some(not) = schema.get("not") {
+            if self.matches(not, instance, depth + 1) {
+                self.fail(path, "Matches a schema in not".to_string());
+            }
+        }
+    }
+
+    fn check_object(
+        &mut self,
+        schema: &serde_json::Map<String, Value>,
+        object: &serde_json::Map<String, Value>,
+        path: &str,
+        depth: usize,
+    ) {
+        let properties = schema.get("properties").and_then(Value::as_object);
+
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    self.fail(path, format!("Missing required property '{}'", name));
+                }
+            }
+        }
+
+        for (name, value) in object {
+            let child = format!("{}/{}", path, escape_pointer(name));
+            match properties.and_then(|p| p.get(name)) {
+                Some(subschema) => self.check(subschema, value, &child, depth + 1),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        self.fail(path, format!("Unexpected property '{}'", name))
+                    }
+                    Some(subschema @ Value::Object(_)) => {
+                        self.check(subschema, value, &child, depth + 1)
+                    }
+                    _ => {}
+                },
+            }
+        }
+
+        if let Some(min) = schema.get("minProperties").and_then(Value::as_u64) {
+            if (object.len() as u64) < min {
+                self.fail(path, format!("Expected at least {} properties", min));
+            }
+        }
+        if let Some(max) = schema.get("maxProperties").and_then(Value::as_u64) {
+            if object.len() as u64 > max {
+                self.fail(path, format!("Expected at most {} properties", max));
+            }
+        }
+    }
+
+    fn check_array(
+        &mut self,
+        schema: &serde_json::Map<String, Value>,
+        items: &[Value],
+        path: &str,
+        depth: usize,
+    ) {
+        let prefix = schema
+            .get("prefixItems")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        for (index, item) in items.iter().enumerate() {
+            let child = format!("{}/{}", path, index);
+            match prefix.get(index) {
+                Some(subschema) => self.check(subschema, item, &child, depth + 1),
+                None => {
+                    if let Some(subschema) = schema.get("items") {
+                        self.check(subschema, item, &child, depth + 1);
+                    }
+                }
+            }
+        }
+
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if (items.len() as u64) < min {
+                self.fail(
+                    path,
+                    format!("Expected at least {} items, got {}", min, items.len()),
+                );
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+            if items.len() as u64 > max {
+                self.fail(
+                    path,
+                    format!("Expected at most {} items, got {}", max, items.len()),
+                );
+            }
+        }
+        if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+            let duplicate = items
+                .iter()
+                .enumerate()
+                .any(|(i, item)| items[..i].contains(item));
+            if duplicate {
+                self.fail(path, "Items are not unique".to_string());
+            }
+        }
+    }
+
+    fn check_string(&mut self, schema: &serde_json::Map<String, Value>, text: &str, path: &str) {
+        let length = text.chars().count() as u64;
+
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if length < min {
+                self.fail(path, format!("Shorter than {} characters", min));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+            if length > max {
+                self.fail(path, format!("Longer than {} characters", max));
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+            match Regex::new(pattern) {
+                Ok(regex) if !regex.is_match(text) => {
+                    self.fail(path, format!("Does not match pattern '{}'", pattern))
+                }
+                Ok(_) => {}
+                Err(_) => self.fail(path, format!("Unsupported pattern '{}'", pattern)),
+            }
+        }
+    }
+
+    fn check_number(&mut self, schema: &serde_json::Map<String, Value>, number: f64, path: &str) {
+        let bound = |keyword: &str| schema.get(keyword).and_then(Value::as_f64);
+
+        if let Some(min) = bound("minimum") {
+            if number < min {
+                self.fail(path, format!("{} is less than the minimum {}", number, min));
+            }
+        }
+        if let Some(max) = bound("maximum") {
+            if number > max {
+                self.fail(
+                    path,
+                    format!("{} is greater than the maximum {}", number, max),
+                );
+            }
+        }
+        if let Some(min) = bound("exclusiveMinimum") {
+            if number <= min {
+                self.fail(path, format!("{} is not greater than {}", number, min));
+            }
+        }
+        if let Some(max) = bound("exclusiveMaximum") {
+            if number >= max {
+                self.fail(path, format!("{} is not less than {}", number, max));
+            }
+        }
+        if let Some(divisor) = bound("multipleOf").filter(|d| *d > 0.0) {
+            let quotient = number / divisor;
+            if (quotient - quotient.round()).abs() > 1e-9 {
+                self.fail(path, format!("{} is not a multiple of {}", number, divisor));
+            }
+        }
+    }
+}
+
+fn is_type(instance: &Value, name: &str) -> bool {
+    match name {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "number" => instance.is_number(),
+        "integer" => {
+            instance.is_i64()
+                || instance.is_u64()
+                || instance.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Escape a property name for use as a JSON Pointer segment
+fn escape_pointer(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::JsonSchemaFormat;
+    use serde_json::json;
+
+    fn person_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string", "minLength": 1},
+                "age": {"type": "integer", "minimum": 0},
+                "tags": {"type": "array", "items": {"$ref": "#/$defs/tag"}}
+            },
+            "required": ["name", "age"],
+            "additionalProperties": false,
+            "$defs": {"tag": {"enum": ["a", "b"]}}
+        })
+    }
+
+    #[test]
+    fn test_valid_instance() {
+        let instance = json!({"name": "Ada", "age": 36, "tags": ["a", "b"]});
+        assert!(validate(&person_schema(), &instance).is_empty());
+    }
+
+    #[test]
+    fn test_violation_paths() {
+        let instance = json!({"name": "", "age": 1.5, "tags": ["a", "c"], "extra": true});
+        let errors = validate(&person_schema(), &instance);
+        let paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert!(paths.contains(&"/name"));
+        assert!(paths.contains(&"/age"));
+        assert!(paths.contains(&"/tags/1"));
+        assert!(errors.iter().any(|e| e.message.contains("'extra'")));
+    }
+
+    #[test]
+    fn test_missing_required() {
+        let errors = validate(&person_schema(), &json!({"name": "Ada"}));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "");
+        assert!(errors[0].message.contains("'age'"));
+    }
+
+    #[test]
+    fn test_combinators() {
+        let schema = json!({"anyOf": [{"type": "string"}, {"type": "null"}]});
+        assert!(validate(&schema, &json!(null)).is_empty());
+        assert_eq!(validate(&schema, &json!(3)).len(), 1);
+
+        let schema = json!({"oneOf": [{"type": "integer"}, {"type": "number"}]});
+        assert_eq!(validate(&schema, &json!(3)).len(), 1);
+        assert!(validate(&schema, &json!(3.5)).is_empty());
+    }
+
+    #[test]
+    fn test_validate_output() {
+        let format = ResponseFormat::JsonSchema {
+            json_schema: JsonSchemaFormat {
+                name: "person".to_string(),
+                description: None,
+                schema: person_schema(),
+                strict: None,
+            },
+        };
+
+        let result = validate_output(&format, r#"{"name": "Ada", "age": 36}"#).unwrap();
+        assert!(result.parsed && result.valid);
+
+        let result = validate_output(&format, "```json\n{}\n```").unwrap();
+        assert!(!result.parsed && !result.valid);
+        assert!(result.parse_error.unwrap().contains("line 1"));
+
+        let result = validate_output(&ResponseFormat::JsonObject, "[1, 2]").unwrap();
+        assert!(result.parsed && !result.valid);
+
+        assert!(validate_output(&ResponseFormat::Text, "anything").is_none());
+    }
+}
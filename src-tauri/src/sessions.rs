@@ -0,0 +1,482 @@
+use crate::commands::get_config_dir;
+use crate::types::{
+    LLMRequest, LLMResponse, Message, Session, SessionSummary, SessionTurn, StreamingResult,
+};
+use lazy_static::lazy_static;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Length of titles derived from the first user message
+const MAX_TITLE_CHARS: usize = 60;
+
+lazy_static! {
+    // Serializes read-modify-write cycles on session files
+    static ref SESSIONS_LOCK: Mutex<()> = Mutex::new(());
+}
+
+fn sessions_dir() -> Result<PathBuf, String> {
+    let dir = get_config_dir()?.join("sessions");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create sessions directory: {}", e))?;
+    Ok(dir)
+}
+
+fn session_file(dir: &Path, id: &str) -> Result<PathBuf, String> {
+    // IDs become file names, so keep them to characters that cannot escape the directory
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid session ID '{}'", id));
+    }
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+fn read_session(dir: &Path, id: &str) -> Result<Session, String> {
+    let path = session_file(dir, id)?;
+    if !path.exists() {
+        return Err(format!("Session '{}' not found", id));
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read session: {}", e))?;
+
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse session: {}", e))
+}
+
+fn write_session(dir: &Path, session: &Session) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(session)
+        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+
+    fs::write(session_file(dir, &session.id)?, content)
+        .map_err(|e| format!("Failed to write session: {}", e))
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn plain_turn(message: &Message) -> SessionTurn {
+    SessionTurn {
+        role: message.role.clone(),
+        content: message.content.clone(),
+        timestamp: now(),
+        request_id: None,
+        model: None,
+        reasoning_content: None,
+        thinking_blocks: Vec::new(),
+        tool_calls: message.tool_calls.clone(),
+        tool_call_id: message.tool_call_id.clone(),
+        usage: None,
+        metrics: None,
+    }
+}
+
+/// Title untitled sessions after their first user message
+fn derive_title(session: &mut Session) {
+    if !session.title.is_empty() {
+        return;
+    }
+
+    if let Some(turn) = session.turns.iter().find(|t| t.role == "user") {
+        let text = turn.content.text();
+        let line = text.lines().next().unwrap_or_default().trim();
+        session.title = match line.char_indices().nth(MAX_TITLE_CHARS) {
+            Some((cut, _)) => format!("{}…", &line[..cut]),
+            None => line.to_string(),
+        };
+    }
+}
+
+fn create_in(
+    dir: &Path,
+    title: Option<String>,
+    system_message: Option<String>,
+) -> Result<Session, String> {
+    let timestamp = now();
+    let turns = system_message
+        .filter(|s| !s.trim().is_empty())
+        .map(|content| {
+            vec![plain_turn(&Message {
+                role: "system".to_string(),
+                content: content.into(),
+                ..Default::default()
+            })]
+        })
+        .unwrap_or_default();
+
+    let session = Session {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: title.unwrap_or_default(),
+        created_at: timestamp,
+        updated_at: timestamp,
+        parent_id: None,
+        turns,
+    };
+
+    write_session(dir, &session)?;
+    Ok(session)
+}
+
+fn append_to(dir: &Path, id: &str, turns: Vec<SessionTurn>) -> Result<Session, String> {
+    let mut session = read_session(dir, id)?;
+    session.turns.extend(turns);
+    session.updated_at = now();
+    derive_title(&mut session);
+
+    write_session(dir, &session)?;
+    Ok(session)
+}
+
+fn fork_in(dir: &Path, id: &str, turn_index: Option<usize>) -> Result<Session, String> {
+    let parent = read_session(dir, id)?;
+    let keep = match turn_index {
+        Some(index) if index >= parent.turns.len() => {
+            return Err(format!(
+                "Turn {} is out of range; session has {} turns",
+                index,
+                parent.turns.len()
+            ))
+        }
+        Some(index) => index + 1,
+        None => parent.turns.len(),
+    };
+
+    let timestamp = now();
+    let session = Session {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: if parent.title.is_empty() {
+            String::new()
+        } else {
+            format!("{} (fork)", parent.title)
+        },
+        created_at: timestamp,
+        updated_at: timestamp,
+        parent_id: Some(parent.id),
+        turns: parent.turns.into_iter().take(keep).collect(),
+    };
+
+    write_session(dir, &session)?;
+    Ok(session)
+}
+
+fn list_in(dir: &Path) -> Result<Vec<SessionSummary>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to list sessions: {}", e))?;
+
+    let mut sessions = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+
+        // Skip unreadable files rather than failing the whole listing
+        let session: Session = match fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+        {
+            Some(session) => session,
+            None => continue,
+        };
+
+        sessions.push(SessionSummary {
+            id: session.id,
+            title: session.title,
+            created_at: session.created_at,
+            updated_at: session.updated_at,
+            parent_id: session.parent_id,
+            turn_count: session.turns.len(),
+        });
+    }
+
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+    Ok(sessions)
+}
+
+fn delete_in(dir: &Path, id: &str) -> Result<(), String> {
+    let path = session_file(dir, id)?;
+    if !path.exists() {
+        return Err(format!("Session '{}' not found", id));
+    }
+
+    fs::remove_file(path).map_err(|e| format!("Failed to delete session: {}", e))
+}
+
+fn request_from(session: Session, request: &LLMRequest) -> LLMRequest {
+    let mut messages: Vec<Message> = session
+        .turns
+        .into_iter()
+        .map(|turn| Message {
+            role: turn.role,
+            content: turn.content,
+            tool_calls: turn.tool_calls,
+            tool_call_id: turn.tool_call_id,
+        })
+        .collect();
+    messages.extend(request.messages.iter().cloned());
+
+    LLMRequest {
+        messages,
+        ..request.clone()
+    }
+}
+
+pub fn get_session(id: &str) -> Result<Session, String> {
+    let _guard = SESSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    read_session(&sessions_dir()?, id)
+}
+
+pub fn create_session(
+    title: Option<String>,
+    system_message: Option<String>,
+) -> Result<Session, String> {
+    let _guard = SESSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    create_in(&sessions_dir()?, title, system_message)
+}
+
+/// Append turns to a session and save it
+pub fn append_turns(id: &str, turns: Vec<SessionTurn>) -> Result<Session, String> {
+    let _guard = SESSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    append_to(&sessions_dir()?, id, turns)
+}
+
+/// Append plain messages (e.g. a hand-written assistant reply or a new system prompt)
+pub fn append_messages(id: &str, messages: &[Message]) -> Result<Session, String> {
+    append_turns(id, messages.iter().map(plain_turn).collect())
+}
+
+/// Copy a session into a new one, keeping turns up to and including `turn_index`
+/// (all turns when `None`) so the conversation can branch from that point
+pub fn fork_session(id: &str, turn_index: Option<usize>) -> Result<Session, String> {
+    let _guard = SESSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    fork_in(&sessions_dir()?, id, turn_index)
+}
+
+/// All sessions, most recently updated first
+pub fn list_sessions() -> Result<Vec<SessionSummary>, String> {
+    let _guard = SESSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    list_in(&sessions_dir()?)
+}
+
+pub fn delete_session(id: &str) -> Result<(), String> {
+    let _guard = SESSIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    delete_in(&sessions_dir()?, id)
+}
+
+/// Build the full request for a session: its stored turns followed by the new
+/// messages in `request`
+pub fn session_request(id: &str, request: &LLMRequest) -> Result<LLMRequest, String> {
+    Ok(request_from(get_session(id)?, request))
+}
+
+/// Assistant turn for a non-streaming response
+pub fn response_turn(request_id: &str, model: &str, response: &LLMResponse) -> SessionTurn {
+    SessionTurn {
+        role: "assistant".to_string(),
+        content: response.content.clone().into(),
+        timestamp: now(),
+        request_id: Some(request_id.to_string()),
+        model: Some(model.to_string()),
+        reasoning_content: response.reasoning_content.clone(),
+        thinking_blocks: response.thinking_blocks.clone(),
+        tool_calls: response.tool_calls.clone(),
+        tool_call_id: None,
+        usage: response.usage.clone(),
+        metrics: Some(response.metrics.clone()),
+    }
+}
+
+/// Assistant turn for a streamed response
+pub fn streaming_turn(model: &str, result: &StreamingResult) -> SessionTurn {
+    SessionTurn {
+        role: "assistant".to_string(),
+        content: result.content.clone().into(),
+        timestamp: now(),
+        request_id: Some(result.request_id.clone()),
+        model: Some(model.to_string()),
+        reasoning_content: result.reasoning_content.clone(),
+        thinking_blocks: result.thinking_blocks.clone(),
+        tool_calls: result.tool_calls.clone(),
+        tool_call_id: None,
+        usage: result.usage.clone(),
+        metrics: Some(result.metrics.clone()),
+    }
+}
+
+/// Store a completed exchange: the new messages the caller sent plus the reply
+pub fn record_exchange(id: &str, sent: &[Message], reply: SessionTurn) -> Result<(), String> {
+    let mut turns: Vec<SessionTurn> = sent.iter().map(plain_turn).collect();
+    turns.push(reply);
+    append_turns(id, turns).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> Message {
+        Message {
+            role: role.to_string(),
+            content: content.to_string().into(),
+            ..Default::default()
+        }
+    }
+
+    fn texts(session: &Session) -> Vec<String> {
+        session.turns.iter().map(|t| t.content.text()).collect()
+    }
+
+    /// A session with a system prompt and one exchange: four turns in total
+    fn conversation(dir: &Path) -> Session {
+        let session = create_in(dir, None, Some("Be brief".to_string())).unwrap();
+        let turns = [
+            message("user", "What is Rust?"),
+            message("assistant", "A language"),
+            message("user", "Who made it?"),
+        ];
+        append_to(dir, &session.id, turns.iter().map(plain_turn).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_create_append_and_title() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let session = create_in(dir, None, Some("  ".to_string())).unwrap();
+        assert!(session.turns.is_empty());
+        assert_eq!(read_session(dir, &session.id).unwrap().title, "");
+
+        let long = format!("{}\nsecond line", "x".repeat(MAX_TITLE_CHARS + 10));
+        append_to(dir, &session.id, vec![plain_turn(&message("user", &long))]).unwrap();
+        let saved = read_session(dir, &session.id).unwrap();
+        assert_eq!(saved.turns.len(), 1);
+        assert_eq!(saved.title, format!("{}…", "x".repeat(MAX_TITLE_CHARS)));
+
+        let session = conversation(dir);
+        assert_eq!(session.title, "What is Rust?");
+        assert_eq!(session.turns[0].role, "system");
+        assert_eq!(list_in(dir).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_fork() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let parent = conversation(dir);
+
+        let fork = fork_in(dir, &parent.id, Some(1)).unwrap();
+        assert_eq!(texts(&fork), vec!["Be brief", "What is Rust?"]);
+        assert_eq!(fork.parent_id.as_deref(), Some(parent.id.as_str()));
+        assert_eq!(fork.title, "What is Rust? (fork)");
+        assert_eq!(read_session(dir, &fork.id).unwrap().turns.len(), 2);
+
+        let copy = fork_in(dir, &parent.id, None).unwrap();
+        assert_eq!(texts(&copy), texts(&parent));
+
+        let last = fork_in(dir, &parent.id, Some(3)).unwrap();
+        assert_eq!(last.turns.len(), 4);
+    }
+
+    #[test]
+    fn test_fork_out_of_range() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let parent = conversation(dir);
+
+        for index in [4, 100] {
+            let err = fork_in(dir, &parent.id, Some(index)).unwrap_err();
+            assert_eq!(
+                err,
+                format!("Turn {} is out of range; session has 4 turns", index)
+            );
+        }
+        let empty = create_in(dir, None, None).unwrap();
+        assert!(fork_in(dir, &empty.id, Some(0)).is_err());
+        // Nothing was written for the failed forks
+        assert_eq!(list_in(dir).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_delete() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let session = conversation(dir);
+
+        delete_in(dir, &session.id).unwrap();
+        assert!(list_in(dir).unwrap().is_empty());
+        assert_eq!(
+            delete_in(dir, &session.id).unwrap_err(),
+            format!("Session '{}' not found", session.id)
+        );
+        assert!(read_session(dir, &session.id).is_err());
+    }
+
+    #[test]
+    fn test_invalid_ids_rejected() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("sessions");
+        fs::create_dir_all(&dir).unwrap();
+        // A file next to the sessions directory that a traversal would reach
+        fs::write(dir.join("../secret.json"), "{}").unwrap();
+
+        for id in [
+            "",
+            "../secret",
+            "..",
+            "a/b",
+            "a\\b",
+            "/etc/passwd",
+            "x.json",
+            "id with space",
+        ] {
+            let expected = format!("Invalid session ID '{}'", id);
+            assert_eq!(session_file(&dir, id).unwrap_err(), expected);
+            assert_eq!(read_session(&dir, id).unwrap_err(), expected);
+            assert_eq!(delete_in(&dir, id).unwrap_err(), expected);
+            assert_eq!(fork_in(&dir, id, None).unwrap_err(), expected);
+            assert_eq!(append_to(&dir, id, Vec::new()).unwrap_err(), expected);
+        }
+        assert!(dir.join("../secret.json").exists());
+        assert!(session_file(&dir, "Ab-9_").is_ok());
+    }
+
+    #[test]
+    fn test_session_request_order() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let session = conversation(dir);
+        let request: LLMRequest = serde_json::from_value(serde_json::json!({
+            "model": "llama",
+            "messages": [
+                {"role": "assistant", "content": "Mozilla"},
+                {"role": "user", "content": "When?"}
+            ],
+            "temperature": 0.5,
+            "max_tokens": 100,
+            "stream": true
+        }))
+        .unwrap();
+
+        let full = request_from(read_session(dir, &session.id).unwrap(), &request);
+        let messages: Vec<(String, String)> = full
+            .messages
+            .iter()
+            .map(|m| (m.role.clone(), m.content.text()))
+            .collect();
+        let expected = [
+            ("system", "Be brief"),
+            ("user", "What is Rust?"),
+            ("assistant", "A language"),
+            ("user", "Who made it?"),
+            ("assistant", "Mozilla"),
+            ("user", "When?"),
+        ];
+        assert_eq!(
+            messages,
+            expected.map(|(role, text)| (role.to_string(), text.to_string()))
+        );
+        assert_eq!(full.model, "llama");
+        assert_eq!(full.max_tokens, 100);
+        assert!(full.stream);
+    }
+}
@@ -0,0 +1,291 @@
+//! Server-sent events decoding, following the WHATWG event stream format:
+//! https://html.spec.whatwg.org/multipage/server-sent-events.html#parsing-an-event-stream
+
+/// One dispatched event
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// `event:` field; `None` means the default `message` type
+    pub event: Option<String>,
+    /// `data:` fields joined by newlines
+    pub data: String,
+    /// Last event ID seen on the stream so far
+    pub id: Option<String>,
+    /// Reconnection time requested with `retry:`, in milliseconds
+    pub retry: Option<u64>,
+}
+
+/// Incremental decoder: feed it body chunks as they arrive and collect the
+/// complete events. Lines may end in `\r\n`, `\n` or `\r`, and a chunk may end
+/// anywhere, including inside a line ending or a UTF-8 sequence, since bytes are
+/// only decoded once their line is complete.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    /// Bytes of the line being received
+    line: Vec<u8>,
+    /// The previous chunk ended in `\r`, so a leading `\n` belongs to that line ending
+    pending_cr: bool,
+    /// Whether anything has been decoded yet (a leading BOM is skipped)
+    started: bool,
+    event: Option<String>,
+    data: Option<String>,
+    last_id: Option<String>,
+    retry: Option<u64>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode a chunk, returning the events it completed
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+        let mut bytes = chunk;
+
+        if self.pending_cr && !bytes.is_empty() {
+            self.pending_cr = false;
+            if let Some(rest) = bytes.strip_prefix(b"\n") {
+                bytes = rest;
+            }
+        }
+
+        while let Some(pos) = bytes.iter().position(|&b| b == b'\n' || b == b'\r') {
+            self.line.extend_from_slice(&bytes[..pos]);
+            let line = std::mem::take(&mut self.line);
+            if let Some(event) = self.process_line(&line) {
+                events.push(event);
+            }
+
+            if bytes[pos] == b'\r' {
+                match bytes.get(pos + 1) {
+                    Some(b'\n') => bytes = &bytes[pos + 2..],
+                    Some(_) => bytes = &bytes[pos + 1..],
+                    None => {
+                        self.pending_cr = true;
+                        bytes = &[];
+                    }
+                }
+            } else {
+                bytes = &bytes[pos + 1..];
+            }
+        }
+        self.line.extend_from_slice(bytes);
+
+        events
+    }
+
+    /// End of stream. Browsers discard an event that is missing its terminating
+    /// blank line, but some servers close the connection right after the last
+    /// `data:` line, so it is delivered instead.
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        let line = std::mem::take(&mut self.line);
+        if !line.is_empty() {
+            if let Some(event) = self.process_line(&line) {
+                return Some(event);
+            }
+        }
+        self.dispatch()
+    }
+
+    fn process_line(&mut self, line: &[u8]) -> Option<SseEvent> {
+        let mut line = line;
+        if !self.started {
+            self.started = true;
+            if let Some(rest) = line.strip_prefix("\u{feff}".as_bytes()) {
+                line = rest;
+            }
+        }
+
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        // Comment, e.g. `: keep-alive`
+        if line[0] == b':' {
+            return None;
+        }
+
+        let line = String::from_utf8_lossy(line);
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line.as_ref(), ""),
+        };
+
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            // Several data lines in one event are joined with newlines
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            },
+            // IDs containing NUL are ignored, per the spec
+            "id" if !value.contains('\0') => self.last_id = Some(value.to_string()),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                self.retry = value.parse().ok();
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    /// Dispatch the buffered event at a blank line; events without data are dropped
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        let data = self.data.take()?;
+
+        Some(SseEvent {
+            event,
+            data,
+            id: self.last_id.clone(),
+            retry: self.retry,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Recorded from an OpenAI-compatible server (vLLM), with a keep-alive comment
+    const CHAT_STREAM: &str = concat!(
+        ": ping\n\n",
+        "data: {\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"\"}}]}\n\n",
+        "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Caf\u{e9} \u{1f600}\"}}]}\n\n",
+        "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\" \u{4f60}\u{597d}\"},\"finish_reason\":\"stop\"}]}\n\n",
+        "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":5,\"completion_tokens\":3}}\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    /// Recorded from the Anthropic Messages API
+    const ANTHROPIC_STREAM: &str = concat!(
+        "event: message_start\n",
+        "data: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":10}}}\n\n",
+        "event: ping\n",
+        "data: {\"type\": \"ping\"}\n\n",
+        "event: content_block_delta\n",
+        "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"\u{3053}\u{3093}\"}}\n\n",
+        "event: message_stop\n",
+        "data: {\"type\":\"message_stop\"}\n\n",
+    );
+
+    fn decode_chunks<'a>(chunks: impl IntoIterator<Item = &'a [u8]>) -> Vec<SseEvent> {
+        let mut decoder = SseDecoder::new();
+        let mut events: Vec<SseEvent> = chunks
+            .into_iter()
+            .flat_map(|chunk| decoder.push(chunk))
+            .collect();
+        events.extend(decoder.finish());
+        events
+    }
+
+    fn decode(stream: &str) -> Vec<SseEvent> {
+        decode_chunks([stream.as_bytes()])
+    }
+
+    /// Deterministic pseudo-random chunk sizes (xorshift), so failures reproduce
+    fn random_chunks(bytes: &[u8], mut seed: u64) -> Vec<&[u8]> {
+        let mut chunks = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let size = (seed % 12) as usize;
+            let (chunk, tail) = rest.split_at(size.min(rest.len()));
+            chunks.push(chunk);
+            rest = tail;
+        }
+        chunks
+    }
+
+    #[test]
+    fn test_recorded_streams() {
+        let events = decode(CHAT_STREAM);
+        assert_eq!(events.len(), 5);
+        assert!(events[1].data.contains("Caf\u{e9} \u{1f600}"));
+        assert_eq!(events[4].data, "[DONE]");
+        assert!(events.iter().all(|e| e.event.is_none()));
+
+        let events = decode(ANTHROPIC_STREAM);
+        let types: Vec<_> = events.iter().map(|e| e.event.as_deref().unwrap()).collect();
+        assert_eq!(
+            types,
+            [
+                "message_start",
+                "ping",
+                "content_block_delta",
+                "message_stop"
+            ]
+        );
+        assert_eq!(events[1].data, "{\"type\": \"ping\"}");
+    }
+
+    #[test]
+    fn test_any_chunking_gives_the_same_events() {
+        for stream in [CHAT_STREAM, ANTHROPIC_STREAM] {
+            let expected = decode(stream);
+            let bytes = stream.as_bytes();
+
+            // Every split point, including inside multi-byte characters
+            for split in 0..=bytes.len() {
+                let (a, b) = bytes.split_at(split);
+                assert_eq!(decode_chunks([a, b]), expected, "split at {}", split);
+            }
+
+            // One byte at a time, then random chunk sizes (with empty chunks)
+            assert_eq!(decode_chunks(bytes.chunks(1)), expected);
+            for seed in 1..200 {
+                assert_eq!(
+                    decode_chunks(random_chunks(bytes, seed)),
+                    expected,
+                    "seed {}",
+                    seed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_line_endings() {
+        for stream in [CHAT_STREAM, ANTHROPIC_STREAM] {
+            let expected = decode(stream);
+            for ending in ["\r\n", "\r"] {
+                let converted = stream.replace('\n', ending);
+                let bytes = converted.as_bytes();
+                assert_eq!(decode(&converted), expected);
+                assert_eq!(decode_chunks(bytes.chunks(1)), expected);
+                for seed in 1..50 {
+                    assert_eq!(decode_chunks(random_chunks(bytes, seed)), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fields() {
+        let stream = "\u{feff}data: first\ndata:second\ndata\n\n\
+            id: 7\nretry: 1500\nevent: update\ndata:  two spaces\n\n\
+            event: no-data\n\n\
+            retry: soon\nid: a\0b\nunknown: field\ndata: last";
+        let events = decode(stream);
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].data, "first\nsecond\n");
+        assert_eq!(events[0].id, None);
+
+        assert_eq!(events[1].event.as_deref(), Some("update"));
+        assert_eq!(events[1].data, " two spaces");
+        assert_eq!(events[1].id.as_deref(), Some("7"));
+        assert_eq!(events[1].retry, Some(1500));
+
+        // An event without data is dropped and its type does not leak into the next;
+        // invalid `retry` and NUL-containing IDs are ignored
+        assert_eq!(events[2].event, None);
+        assert_eq!(events[2].data, "last");
+        assert_eq!(events[2].id.as_deref(), Some("7"));
+        assert_eq!(events[2].retry, Some(1500));
+    }
+}
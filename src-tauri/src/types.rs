@@ -9,16 +9,178 @@ pub struct Endpoint {
     pub api_key: Option<String>,
     pub headers: Vec<(String, String)>,
     pub model: String,
+    /// Wire protocol spoken by the endpoint
+    #[serde(rename = "apiStyle", default)]
+    pub api_style: ApiStyle,
+    /// Provider family of the endpoint's models; `auto` detects it per request
+    #[serde(default)]
+    pub provider: ProviderSetting,
+    /// Timeouts for requests to this endpoint; older saved endpoints get the defaults
+    #[serde(default)]
+    pub timeouts: TimeoutConfig,
+    /// Retries of failed requests; by default a request is sent once
+    #[serde(default)]
+    pub retry: RetryPolicy,
 }
 
-/// Reasoning model providers
+/// When and how often a failed request is sent again. Only failures before any of the
+/// response body was read are retried, so a stream is never restarted midway.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first; 1 disables retries
+    #[serde(rename = "maxAttempts")]
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubling for each retry after it
+    #[serde(rename = "initialBackoffMs")]
+    pub initial_backoff_ms: u64,
+    /// Cap on the backoff. A longer wait requested by the server (`Retry-After`,
+    /// `x-ratelimit-reset-*`) fails the request instead of being cut short.
+    #[serde(rename = "maxBackoffMs")]
+    pub max_backoff_ms: u64,
+    /// Response statuses worth retrying
+    #[serde(rename = "retryStatuses")]
+    pub retry_statuses: Vec<u16>,
+    /// Failures without a response worth retrying
+    #[serde(rename = "retryErrors")]
+    pub retry_errors: Vec<RetryableError>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            retry_statuses: vec![408, 429, 500, 502, 503, 504],
+            retry_errors: vec![RetryableError::Connect, RetryableError::Timeout],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryableError {
+    /// The connection could not be opened (refused, reset, DNS failure, connect timeout)
+    Connect,
+    /// No response headers within the read or total timeout
+    Timeout,
+}
+
+/// Per-endpoint timeouts in milliseconds; `None` disables one
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TimeoutConfig {
+    /// Establishing a new connection (TCP and TLS)
+    #[serde(rename = "connectMs")]
+    pub connect_ms: Option<u64>,
+    /// From sending the request (connecting included) to its response headers. Without
+    /// streaming, reading the body then gets a window of the same length of its own.
+    #[serde(rename = "readMs")]
+    pub read_ms: Option<u64>,
+    /// The whole request, from sending it to the end of the body
+    #[serde(rename = "totalMs")]
+    pub total_ms: Option<u64>,
+    /// Longest gap between two chunks of a streamed body
+    #[serde(rename = "idleStreamMs")]
+    pub idle_stream_ms: Option<u64>,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            connect_ms: Some(10_000),
+            // Without streaming nothing arrives until generation ends
+            read_ms: Some(600_000),
+            total_ms: None,
+            idle_stream_ms: Some(120_000),
+        }
+    }
+}
+
+/// Wire protocol used to talk to an endpoint
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiStyle {
+    /// OpenAI-compatible `{url}/chat/completions`
+    #[default]
+    ChatCompletions,
+    /// Anthropic Messages API `{url}/messages`
+    AnthropicMessages,
+    /// Google Gemini `{url}/models/{model}:generateContent`
+    GeminiGenerateContent,
+    /// OpenAI Responses API `{url}/responses`
+    Responses,
+    /// Legacy text completions `{url}/completions` (base models, raw prompts)
+    Completions,
+}
+
+/// Reasoning model providers
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ReasoningProvider {
     OpenAI,
     DeepSeek,
     Qwen,
     Claude,
+    Gemini,
+}
+
+/// Provider configured on an endpoint
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderSetting {
+    /// Detect from the API style, model name and `/models` metadata
+    #[default]
+    Auto,
+    /// Plain chat model: no reasoning parameters are sent
+    Generic,
+    OpenAI,
+    DeepSeek,
+    Qwen,
+    Claude,
+    Gemini,
+}
+
+impl ProviderSetting {
+    /// Reasoning provider for an explicit setting (`None` for auto and generic)
+    pub fn reasoning_provider(self) -> Option<ReasoningProvider> {
+        match self {
+            ProviderSetting::Auto | ProviderSetting::Generic => None,
+            ProviderSetting::OpenAI => Some(ReasoningProvider::OpenAI),
+            ProviderSetting::DeepSeek => Some(ReasoningProvider::DeepSeek),
+            ProviderSetting::Qwen => Some(ReasoningProvider::Qwen),
+            ProviderSetting::Claude => Some(ReasoningProvider::Claude),
+            ProviderSetting::Gemini => Some(ReasoningProvider::Gemini),
+        }
+    }
+}
+
+/// Where a request's provider came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderSource {
+    /// Set explicitly on the endpoint
+    Endpoint,
+    /// Implied by the endpoint's API style
+    ApiStyle,
+    /// Matched from the model name
+    ModelName,
+    /// Matched from the endpoint's `/models` entry for the model
+    ModelMetadata,
+    /// Nothing matched; plain chat completions
+    Default,
+}
+
+/// Provider and adapter chosen for a request, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderResolution {
+    /// Adapter that built the request and parsed the response
+    pub adapter: String,
+    pub provider: Option<ReasoningProvider>,
+    pub source: ProviderSource,
+    /// Human-readable explanation, e.g. which pattern or metadata field matched
+    pub reason: String,
 }
 
 /// Reasoning configuration for supported models
@@ -52,12 +214,211 @@ pub struct LLMRequest {
     /// Reasoning configuration for supported models
     #[serde(rename = "reasoningConfig")]
     pub reasoning_config: Option<ReasoningConfig>,
+    /// Functions the model may call
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<Tool>,
+    /// OpenAI-style `tool_choice`: "auto", "none", "required" or
+    /// `{"type": "function", "function": {"name": ...}}`
+    #[serde(alias = "toolChoice", default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<serde_json::Value>,
+    /// Structured output mode; the response content is validated against it
+    #[serde(
+        alias = "responseFormat",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub response_format: Option<ResponseFormat>,
+    /// Raw prompt for the completions API style, sent without a chat template.
+    /// When absent, the message texts are joined into the prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Return the prompt in front of the completion (completions API style)
+    #[serde(default)]
+    pub echo: bool,
+    /// Text that follows the completion, for fill-in-the-middle (completions API style)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+    /// Return the log probability of each output token
+    #[serde(default)]
+    pub logprobs: bool,
+    /// Also return the N most likely alternatives at each position
+    #[serde(
+        alias = "topLogprobs",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub top_logprobs: Option<u32>,
+}
+
+/// OpenAI-style `response_format`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    Text,
+    /// Any JSON object
+    JsonObject,
+    /// JSON matching a user-supplied schema
+    JsonSchema {
+        json_schema: JsonSchemaFormat,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub schema: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
 }
 
+/// Result of checking response content against the requested `response_format`
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputValidation {
+    /// Whether the content parsed as JSON
+    pub parsed: bool,
+    /// Whether it parsed and matched the schema (or, for `json_object`, was an object)
+    pub valid: bool,
+    /// JSON parse error, with line and column
+    #[serde(rename = "parseError")]
+    pub parse_error: Option<String>,
+    pub errors: Vec<SchemaViolation>,
+    /// Time spent parsing and validating, not included in the request latency
+    #[serde(rename = "validationMs")]
+    pub validation_ms: f64,
+}
+
+/// One place where the output broke the schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaViolation {
+    /// JSON Pointer to the offending value (empty for the document root)
+    pub path: String,
+    pub message: String,
+}
+
+/// Chat message in the OpenAI wire format, so it serializes straight into request bodies
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Message {
     pub role: String,
-    pub content: String,
+    #[serde(default)]
+    pub content: MessageContent,
+    /// Calls requested by an assistant message
+    #[serde(alias = "toolCalls", default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// ID of the call a `tool` message answers
+    #[serde(alias = "toolCallId", default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+/// Message content: a plain string or an array of typed parts (text, images, audio)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl Default for MessageContent {
+    fn default() -> Self {
+        MessageContent::Text(String::new())
+    }
+}
+
+impl From<String> for MessageContent {
+    fn from(text: String) -> Self {
+        MessageContent::Text(text)
+    }
+}
+
+impl MessageContent {
+    /// The text of the message, with text parts joined by newlines and media left out
+    pub fn text(&self) -> String {
+        match self {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            MessageContent::Text(text) => text.is_empty(),
+            MessageContent::Parts(parts) => parts.is_empty(),
+        }
+    }
+}
+
+/// One part of multimodal content, in the OpenAI chat completions format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+    InputAudio { input_audio: InputAudio },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageUrl {
+    /// `http(s)://` or `data:` URL. Local paths (plain or `file://`) are read and sent
+    /// as base64 data URLs.
+    pub url: String,
+    /// `low`, `high` or `auto`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputAudio {
+    /// Base64-encoded audio, or a `file://` URL to a local file to encode
+    pub data: String,
+    /// `wav` or `mp3`
+    pub format: String,
+}
+
+/// Function tool definition (`{"type": "function", "function": {...}}`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tool {
+    #[serde(rename = "type", default = "function_type")]
+    pub kind: String,
+    pub function: FunctionDefinition,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionDefinition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// JSON Schema for the arguments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+}
+
+/// A call the model made; `arguments` is the raw JSON string as generated
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolCall {
+    #[serde(default)]
+    pub id: String,
+    #[serde(rename = "type", default = "function_type")]
+    pub kind: String,
+    pub function: FunctionCall,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FunctionCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: String,
+}
+
+fn function_type() -> String {
+    "function".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,6 +432,67 @@ pub struct LLMResponse {
     pub thinking_blocks: Vec<ThinkingBlock>,
     /// Detected reasoning provider
     pub reasoning_provider: Option<ReasoningProvider>,
+    /// Whether the request was cancelled before the response arrived
+    #[serde(default)]
+    pub cancelled: bool,
+    /// Backend-measured timing (TTFT equals total latency without streaming)
+    #[serde(default)]
+    pub metrics: PerformanceMetrics,
+    #[serde(rename = "toolCalls", default)]
+    pub tool_calls: Vec<ToolCall>,
+    /// Set when the request asked for JSON output
+    #[serde(default)]
+    pub validation: Option<OutputValidation>,
+    /// How the provider was chosen for this request
+    #[serde(rename = "providerResolution", default)]
+    pub provider_resolution: Option<ProviderResolution>,
+    /// Per-token log probabilities, when the request asked for them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logprobs: Vec<TokenLogprob>,
+    /// Summary of `logprobs`
+    #[serde(rename = "logprobStats", default)]
+    pub logprob_stats: Option<LogprobStats>,
+    /// Problems that did not fail the request, e.g. it could not be saved to history
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Log probability of one output token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenLogprob {
+    pub token: String,
+    pub logprob: f64,
+    /// Most likely tokens at this position, most likely first
+    #[serde(rename = "topLogprobs", default, skip_serializing_if = "Vec::is_empty")]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopLogprob {
+    pub token: String,
+    pub logprob: f64,
+}
+
+/// Confidence summary of a response, for comparing deployments of the same model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogprobStats {
+    #[serde(rename = "tokenCount")]
+    pub token_count: usize,
+    #[serde(rename = "meanLogprob")]
+    pub mean_logprob: f64,
+    /// `exp(-meanLogprob)`
+    pub perplexity: f64,
+    /// Tokens the model was least sure of, least likely first
+    #[serde(rename = "lowestTokens")]
+    pub lowest_tokens: Vec<LowConfidenceToken>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LowConfidenceToken {
+    /// Position in `logprobs`
+    pub index: usize,
+    pub token: String,
+    pub logprob: f64,
 }
 
 /// Thinking block from Claude API
@@ -88,31 +510,534 @@ pub struct UsageMetrics {
     pub total_tokens: u32,
     /// Reasoning tokens (OpenAI only - counts but not exposes content)
     pub reasoning_tokens: Option<u32>,
+    /// Prompt tokens written to the prompt cache (Anthropic)
+    #[serde(default)]
+    pub cache_creation_tokens: Option<u32>,
+    /// Prompt tokens served from the prompt cache
+    #[serde(default)]
+    pub cache_read_tokens: Option<u32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PerformanceMetrics {
+    #[serde(rename = "ttftMs")]
     pub ttft_ms: f64,
+    #[serde(rename = "avgTpotMs")]
     pub avg_tpot_ms: Option<f64>,
+    #[serde(rename = "totalLatencyMs")]
     pub total_latency_ms: f64,
+    #[serde(rename = "totalTokens")]
     pub total_tokens: usize,
+    #[serde(rename = "tokensPerSecond")]
     pub tokens_per_second: Option<f64>,
+    /// Time to first reasoning/thinking token (streaming reasoning models only)
+    #[serde(rename = "timeToFirstReasoningMs", default)]
+    pub time_to_first_reasoning_ms: Option<f64>,
+    /// Time to first answer token, after any reasoning (streaming only)
+    #[serde(rename = "timeToFirstAnswerMs", default)]
+    pub time_to_first_answer_ms: Option<f64>,
+    /// Number of SSE chunks that carried reasoning; not a token count, for which see
+    /// `usage.reasoning_tokens`. Older history entries stored it as `reasoningTokens`.
+    #[serde(rename = "reasoningChunks", alias = "reasoningTokens", default)]
+    pub reasoning_chunks: usize,
+    /// Number of SSE chunks that carried output (0 for non-streaming requests)
+    #[serde(rename = "streamChunks", default)]
+    pub stream_chunks: usize,
+    /// When each streamed tool call started and finished
+    #[serde(rename = "toolCalls", default)]
+    pub tool_calls: Vec<ToolCallTiming>,
+    /// Size of the request body sent, including any base64-encoded media
+    #[serde(rename = "requestBytes", default)]
+    pub request_bytes: usize,
+    /// Whether the request reused a pooled connection, so no TCP/TLS setup is in TTFT
+    #[serde(rename = "connectionReused", default)]
+    pub connection_reused: Option<bool>,
+    /// Where the time went on the network, from DNS lookup to the end of the body
+    #[serde(default)]
+    pub network: Option<NetworkTimings>,
+    /// Attempts the request took. The other metrics cover only the last attempt.
+    #[serde(default)]
+    pub retries: Option<RetryStats>,
+}
+
+/// Attempts made under the endpoint's retry policy and the time lost to failed ones
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetryStats {
+    pub attempts: u32,
+    /// Time spent in the attempts that failed
+    #[serde(rename = "failedAttemptsMs")]
+    pub failed_attempts_ms: f64,
+    /// Time spent waiting between attempts
+    #[serde(rename = "waitMs")]
+    pub wait_ms: f64,
+    /// Why each failed attempt failed, in order
+    pub errors: Vec<String>,
+}
+
+/// When each network phase of a request finished, in milliseconds since it was sent
+/// (cumulative, like curl's `-w` timings). A phase that did not happen is `None`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkTimings {
+    /// DNS lookup done; `None` on a reused connection or an IP address URL
+    #[serde(rename = "dnsMs")]
+    pub dns_ms: Option<f64>,
+    /// New connection ready: TCP connect plus, on https, the TLS handshake. reqwest
+    /// exposes no point between the two, so they are reported together.
+    #[serde(rename = "connectMs")]
+    pub connect_ms: Option<f64>,
+    /// Request body handed to the connection
+    #[serde(rename = "uploadMs")]
+    pub upload_ms: Option<f64>,
+    /// Response status and headers received
+    #[serde(rename = "headersMs")]
+    pub headers_ms: Option<f64>,
+    /// First byte of the response body
+    #[serde(rename = "firstByteMs")]
+    pub first_byte_ms: Option<f64>,
+    /// First output token (the end of the body without streaming, as for TTFT)
+    #[serde(rename = "firstTokenMs")]
+    pub first_token_ms: Option<f64>,
+    /// Body fully received
+    #[serde(rename = "transferEndMs")]
+    pub transfer_end_ms: Option<f64>,
 }
 
+/// Timing of one streamed tool call, relative to the request start
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TokenEvent {
-    pub token: String,
-    pub timestamp: i64,
+pub struct ToolCallTiming {
+    /// Position in the result's `toolCalls`
+    pub index: usize,
+    /// First argument fragment (or the call header, if it came first)
+    #[serde(rename = "startedMs")]
+    pub started_ms: f64,
+    /// Last argument fragment of a finished call; `None` if the stream was cut off mid-call
+    #[serde(rename = "completedMs")]
+    pub completed_ms: Option<f64>,
+}
+
+/// Result of a streaming request, timed at the network layer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamingResult {
+    #[serde(rename = "requestId")]
+    pub request_id: String,
+    /// Content received so far (partial if cancelled)
+    pub content: String,
+    /// Streamed reasoning content from DeepSeek/Qwen (`delta.reasoning_content`)
+    #[serde(rename = "reasoningContent")]
+    pub reasoning_content: Option<String>,
+    /// Thinking blocks streamed by the Anthropic Messages API
+    #[serde(rename = "thinkingBlocks")]
+    pub thinking_blocks: Vec<ThinkingBlock>,
+    /// Server-reported usage from the final stream chunk(s)
+    pub usage: Option<UsageMetrics>,
+    pub metrics: PerformanceMetrics,
+    /// Whether the stream was cancelled before completion
+    pub cancelled: bool,
+    /// Finish reason from the final chunk(s), if the server sent one
+    #[serde(rename = "finishReason", default)]
+    pub finish_reason: Option<String>,
+    /// Tool calls reassembled from streamed fragments
+    #[serde(rename = "toolCalls", default)]
+    pub tool_calls: Vec<ToolCall>,
+    /// Set when the request asked for JSON output
+    #[serde(default)]
+    pub validation: Option<OutputValidation>,
+    /// How the provider was chosen for this request
+    #[serde(rename = "providerResolution", default)]
+    pub provider_resolution: Option<ProviderResolution>,
+    /// Per-token log probabilities, when the request asked for them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logprobs: Vec<TokenLogprob>,
+    /// Summary of `logprobs`
+    #[serde(rename = "logprobStats", default)]
+    pub logprob_stats: Option<LogprobStats>,
+    /// Problems that did not fail the request, e.g. it could not be saved to history
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Failure of a command, sent to the frontend in place of a bare message so it can
+/// tell e.g. a bad API key from a rate limit or an overflowing prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppError {
+    pub kind: ErrorKind,
+    /// Human-readable summary, including the status and provider message when known
+    pub message: String,
+    /// HTTP status of the failed response; `None` when no response was received
+    pub status: Option<u16>,
+    /// Provider's error code or type, e.g. `context_length_exceeded` or `overloaded_error`
+    #[serde(rename = "providerCode")]
+    pub provider_code: Option<String>,
+    /// Message parsed out of the provider's error body
+    #[serde(rename = "providerMessage")]
+    pub provider_message: Option<String>,
+    /// Headers of the failed response, for request IDs and rate limit details
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// Whether sending the same request again may succeed
+    pub retryable: bool,
+    /// Attempts made under the endpoint's retry policy, including the one that failed
+    /// (boxed to keep `Result<_, AppError>` small)
+    #[serde(default)]
+    pub retries: Option<Box<RetryStats>>,
+    /// Problems besides the failure itself, e.g. the request could not be saved to
+    /// history (boxed for the same reason; usually `None`, so nothing is allocated)
+    #[allow(clippy::box_collection)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<Box<Vec<String>>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Missing or invalid API key, or no access to the model (401/403)
+    Auth,
+    /// Unknown route or model (404)
+    NotFound,
+    /// Too many requests or tokens, or the quota is used up (429)
+    RateLimit,
+    /// The prompt plus the requested output does not fit the model's context window
+    ContextLength,
+    /// The request was rejected as invalid, by the server or before it was sent
+    InvalidRequest,
+    /// The server failed or is overloaded (5xx, Anthropic's 529)
+    Server,
+    /// A connect, read, total or stream-idle timeout expired
+    Timeout,
+    /// The connection could not be opened or broke while reading the response
+    Connection,
+    /// The response or a stream event could not be parsed
+    MalformedResponse,
+    /// Anything else: local configuration, storage, events
+    Other,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RequestHistoryItem {
     pub id: String,
     pub timestamp: i64,
+    #[serde(default)]
+    pub endpoint_id: String,
     pub endpoint_name: String,
+    #[serde(default)]
+    pub endpoint_url: String,
     pub model: String,
     pub prompt: String,
     pub response: String,
     pub metrics: PerformanceMetrics,
     pub stream: bool,
+    /// Full request as sent to the endpoint
+    #[serde(default)]
+    pub request: Option<LLMRequest>,
+    /// Parsed response (non-streaming requests only)
+    #[serde(default)]
+    pub llm_response: Option<LLMResponse>,
+    /// Streamed result with its usage, reasoning and tool calls (streaming requests only)
+    #[serde(default)]
+    pub streaming_result: Option<StreamingResult>,
+    /// Whether the request was cancelled before completion
+    #[serde(default)]
+    pub cancelled: bool,
+    /// Error text if the request failed
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Load-test parameters for `run_benchmark`. Streaming follows `LLMRequest::stream`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkConfig {
+    #[serde(rename = "totalRequests")]
+    pub total_requests: usize,
+    /// Maximum number of requests in flight
+    pub concurrency: usize,
+    /// Fixed arrival rate, kept however many requests are still in flight; when unset
+    /// requests start as soon as a slot frees up
+    #[serde(rename = "targetQps", default)]
+    pub target_qps: Option<f64>,
+    /// TTFT a successful request must meet to count towards goodput
+    #[serde(rename = "sloTtftMs", default)]
+    pub slo_ttft_ms: Option<f64>,
+    /// TPOT a successful request must meet to count towards goodput
+    #[serde(rename = "sloTpotMs", default)]
+    pub slo_tpot_ms: Option<f64>,
+}
+
+/// Outcome of a single benchmark request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkSample {
+    pub index: usize,
+    /// When the request was sent, relative to the benchmark start
+    #[serde(rename = "startOffsetMs")]
+    pub start_offset_ms: f64,
+    /// How long the request waited for a free slot after its scheduled release; only
+    /// with a target QPS
+    #[serde(rename = "queueMs", default)]
+    pub queue_ms: Option<f64>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub usage: Option<UsageMetrics>,
+    pub metrics: PerformanceMetrics,
+}
+
+/// Distribution of a latency metric across benchmark samples, in milliseconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorCount {
+    pub message: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    #[serde(rename = "benchmarkId")]
+    pub benchmark_id: String,
+    pub config: BenchmarkConfig,
+    /// Requests that ran to completion (successfully or not)
+    pub completed: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Whether the benchmark was cancelled before all requests were sent
+    pub cancelled: bool,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: f64,
+    #[serde(rename = "errorRate")]
+    pub error_rate: f64,
+    /// Completed requests per second
+    #[serde(rename = "requestsPerSecond")]
+    pub requests_per_second: f64,
+    /// Successful requests per second that met the configured SLOs
+    pub goodput: f64,
+    /// Output tokens per second summed across successful requests
+    #[serde(rename = "outputTokensPerSecond")]
+    pub output_tokens_per_second: f64,
+    pub ttft: Option<LatencyStats>,
+    pub tpot: Option<LatencyStats>,
+    /// End-to-end latency of successful requests
+    pub latency: Option<LatencyStats>,
+    /// Queueing delay of every request, with a target QPS
+    #[serde(default)]
+    pub queue: Option<LatencyStats>,
+    /// Requests that needed more than one attempt; their latency covers the last one
+    #[serde(default)]
+    pub retried: usize,
+    /// Time lost to failed attempts and waiting between them, summed across requests
+    #[serde(rename = "retryMs", default)]
+    pub retry_ms: f64,
+    pub errors: Vec<ErrorCount>,
+    pub samples: Vec<BenchmarkSample>,
+}
+
+/// Emitted on `benchmark_progress` after each benchmark request completes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkProgress {
+    #[serde(rename = "benchmarkId")]
+    pub benchmark_id: String,
+    pub completed: usize,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    #[serde(rename = "elapsedMs")]
+    pub elapsed_ms: f64,
+}
+
+/// Options for replaying a JSONL file with `run_batch`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchConfig {
+    /// Maximum number of lines in flight; results are still written in input order
+    pub concurrency: usize,
+    /// Force streaming on or off for every line instead of using each line's `stream`
+    #[serde(default)]
+    pub stream: Option<bool>,
+}
+
+/// One line of a batch results file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResult {
+    /// 1-based line number in the input file
+    pub line: usize,
+    /// `custom_id` from OpenAI Batch API style input lines
+    #[serde(rename = "customId")]
+    pub custom_id: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub content: String,
+    #[serde(rename = "reasoningContent")]
+    pub reasoning_content: Option<String>,
+    /// Finish reason; streamed lines only have one if the server sent it
+    #[serde(rename = "finishReason")]
+    pub finish_reason: Option<String>,
+    #[serde(rename = "toolCalls", default)]
+    pub tool_calls: Vec<ToolCall>,
+    pub usage: Option<UsageMetrics>,
+    pub metrics: PerformanceMetrics,
+    /// Set when the line asked for JSON output
+    pub validation: Option<OutputValidation>,
+}
+
+/// Emitted on `batch_progress` as each line's result is written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchProgress {
+    #[serde(rename = "batchId")]
+    pub batch_id: String,
+    pub completed: usize,
+    pub total: usize,
+    pub result: BatchResult,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchSummary {
+    #[serde(rename = "batchId")]
+    pub batch_id: String,
+    #[serde(rename = "inputPath")]
+    pub input_path: String,
+    #[serde(rename = "outputPath")]
+    pub output_path: String,
+    /// Number of non-empty input lines
+    pub total: usize,
+    pub completed: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Successful lines whose output failed JSON/schema validation
+    #[serde(rename = "invalidOutputs")]
+    pub invalid_outputs: usize,
+    /// Whether the batch was cancelled before every line was replayed
+    pub cancelled: bool,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: f64,
+    pub ttft: Option<LatencyStats>,
+    /// End-to-end latency of successful lines
+    pub latency: Option<LatencyStats>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffSegment {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// Word-level diff of one endpoint's content against the baseline endpoint's
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentDiff {
+    /// Index of the baseline entry (the first successful endpoint)
+    #[serde(rename = "baselineIndex")]
+    pub baseline_index: usize,
+    pub index: usize,
+    /// Share of text in common with the baseline, from 0.0 to 1.0
+    pub similarity: f64,
+    pub segments: Vec<DiffSegment>,
+}
+
+/// One endpoint's side of a comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareEntry {
+    /// Position in the `endpoints` list, used to tag `compare_token` events
+    pub index: usize,
+    #[serde(rename = "endpointId")]
+    pub endpoint_id: String,
+    #[serde(rename = "endpointName")]
+    pub endpoint_name: String,
+    pub model: String,
+    pub success: bool,
+    pub error: Option<AppError>,
+    pub cancelled: bool,
+    pub content: String,
+    #[serde(rename = "reasoningContent")]
+    pub reasoning_content: Option<String>,
+    pub usage: Option<UsageMetrics>,
+    pub metrics: PerformanceMetrics,
+    /// Problems that did not fail the request, e.g. it could not be saved to history
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareResult {
+    #[serde(rename = "compareId")]
+    pub compare_id: String,
+    pub entries: Vec<CompareEntry>,
+    pub diffs: Vec<ContentDiff>,
+}
+
+/// Emitted on `compare_token` for every streamed token, tagged with its endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareToken {
+    #[serde(rename = "compareId")]
+    pub compare_id: String,
+    pub index: usize,
+    #[serde(rename = "endpointId")]
+    pub endpoint_id: String,
+    pub token: String,
+    /// Whether this is reasoning/thinking output rather than answer content
+    pub reasoning: bool,
+}
+
+/// One message in a conversation session. Assistant turns also keep their
+/// reasoning and the metrics of the request that produced them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTurn {
+    pub role: String,
+    pub content: MessageContent,
+    pub timestamp: i64,
+    #[serde(rename = "requestId", default)]
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(rename = "reasoningContent", default)]
+    pub reasoning_content: Option<String>,
+    #[serde(rename = "thinkingBlocks", default)]
+    pub thinking_blocks: Vec<ThinkingBlock>,
+    #[serde(default)]
+    pub usage: Option<UsageMetrics>,
+    #[serde(default)]
+    pub metrics: Option<PerformanceMetrics>,
+    #[serde(rename = "toolCalls", default)]
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(rename = "toolCallId", default)]
+    pub tool_call_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: String,
+    pub title: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: i64,
+    /// Session this one was forked from
+    #[serde(rename = "parentId", default)]
+    pub parent_id: Option<String>,
+    pub turns: Vec<SessionTurn>,
+}
+
+/// Session metadata returned by `list_sessions`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub title: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: i64,
+    #[serde(rename = "parentId")]
+    pub parent_id: Option<String>,
+    #[serde(rename = "turnCount")]
+    pub turn_count: usize,
 }
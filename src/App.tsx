@@ -7,7 +7,8 @@ import { ResponseDisplay } from './components/ResponseDisplay';
 import { EndpointModal } from './components/EndpointModal';
 import { useEndpointStore } from './stores/endpointStore';
 import { useLLMRequest } from './hooks/useLLMRequest';
-import { detectProvider } from './utils/provider';
+import { endpointProvider } from './utils/provider';
+import { errorTitle } from './utils/errors';
 import { Endpoint, Message, ReasoningConfig, ReasoningProvider } from './types';
 import './styles/globals.css';
 
@@ -26,13 +27,13 @@ function App() {
   const [reasoningProvider, setReasoningProvider] = useState<ReasoningProvider | null>(null);
 
   const { selectedEndpoint, saveEndpoint } = useEndpointStore();
-  const { isLoading, response, metrics, error, reasoningContent, thinkingBlocks, reasoningProvider: responseProvider, sendRequest, clearResponse, dismissError } =
+  const { isLoading, response, metrics, error, warnings, reasoningContent, thinkingBlocks, reasoningProvider: responseProvider, sendRequest, cancelRequest, clearResponse, dismissError, dismissWarnings } =
     useLLMRequest();
 
   // Detect reasoning provider when endpoint changes
   useEffect(() => {
     if (selectedEndpoint) {
-      const provider = detectProvider(selectedEndpoint.model);
+      const provider = endpointProvider(selectedEndpoint);
       setReasoningProvider(provider);
     } else {
       setReasoningProvider(null);
@@ -132,6 +133,7 @@ function App() {
 
           <PromptInput
             onSubmit={handleSubmit}
+            onCancel={cancelRequest}
             isLoading={isLoading}
             disabled={!selectedEndpoint}
           />
@@ -143,8 +145,14 @@ function App() {
         <div className="fixed bottom-4 right-4 bg-destructive text-destructive-foreground px-4 py-3 rounded-lg shadow-lg max-w-md animate-in slide-in-from-bottom-2 fade-in duration-300">
           <div className="flex items-start justify-between gap-3">
             <div className="flex-1">
-              <p className="font-semibold mb-1">Error</p>
-              <p className="text-sm">{error}</p>
+              <p className="font-semibold mb-1">{errorTitle(error)}</p>
+              <p className="text-sm">{error.providerMessage ?? error.message}</p>
+              {error.retryable && (
+                <p className="text-xs mt-1 opacity-80">This may succeed if you try again</p>
+              )}
+              {error.warnings?.map((warning) => (
+                <p key={warning} className="text-xs mt-1 opacity-80">{warning}</p>
+              ))}
             </div>
             <button
               onClick={dismissError}
@@ -157,6 +165,26 @@ function App() {
         </div>
       )}
 
+      {/* Warnings: the request succeeded, but e.g. was not saved to history */}
+      {!error && warnings.length > 0 && (
+        <div className="fixed bottom-4 right-4 bg-secondary text-secondary-foreground border px-4 py-3 rounded-lg shadow-lg max-w-md animate-in slide-in-from-bottom-2 fade-in duration-300">
+          <div className="flex items-start justify-between gap-3">
+            <div className="flex-1 space-y-1">
+              {warnings.map((warning) => (
+                <p key={warning} className="text-sm">{warning}</p>
+              ))}
+            </div>
+            <button
+              onClick={dismissWarnings}
+              className="shrink-0 hover:bg-secondary-foreground/10 rounded p-0.5 transition-colors"
+              aria-label="Dismiss warnings"
+            >
+              <X className="w-4 h-4" />
+            </button>
+          </div>
+        </div>
+      )}
+
       <EndpointModal
         isOpen={isEndpointModalOpen}
         onClose={() => setIsEndpointModalOpen(false)}
@@ -3,8 +3,9 @@ import { X, Plus, Trash2, RefreshCw, CheckCircle2, XCircle, Loader2 } from 'luci
 import { Button } from './UI/Button';
 import { Input } from './UI/Input';
 import { Label } from './UI/Label';
-import { Endpoint } from '../types';
+import { ApiStyle, DEFAULT_RETRY_POLICY, DEFAULT_TIMEOUTS, Endpoint, ProviderSetting, RetryPolicy, TimeoutConfig } from '../types';
 import { invoke } from '@tauri-apps/api/tauri';
+import { toAppError } from '../utils/errors';
 
 interface EndpointModalProps {
   isOpen: boolean;
@@ -23,6 +24,10 @@ export const EndpointModal: React.FC<EndpointModalProps> = ({
   const [url, setUrl] = useState('');
   const [apiKey, setApiKey] = useState('');
   const [model, setModel] = useState('gpt-3.5-turbo');
+  const [apiStyle, setApiStyle] = useState<ApiStyle>('chat_completions');
+  const [provider, setProvider] = useState<ProviderSetting>('auto');
+  const [timeouts, setTimeouts] = useState<TimeoutConfig>(DEFAULT_TIMEOUTS);
+  const [retry, setRetry] = useState<RetryPolicy>(DEFAULT_RETRY_POLICY);
   const [headers, setHeaders] = useState<[string, string][]>([]);
   const [availableModels, setAvailableModels] = useState<string[]>([]);
   const [isLoadingModels, setIsLoadingModels] = useState(false);
@@ -35,12 +40,20 @@ export const EndpointModal: React.FC<EndpointModalProps> = ({
       setUrl(endpoint.url);
       setApiKey(endpoint.apiKey || '');
       setModel(endpoint.model);
+      setApiStyle(endpoint.apiStyle || 'chat_completions');
+      setProvider(endpoint.provider || 'auto');
+      setTimeouts(endpoint.timeouts || DEFAULT_TIMEOUTS);
+      setRetry(endpoint.retry || DEFAULT_RETRY_POLICY);
       setHeaders(endpoint.headers);
     } else {
       setName('');
       setUrl('https://api.openai.com/v1');
       setApiKey('');
       setModel('gpt-3.5-turbo');
+      setApiStyle('chat_completions');
+      setProvider('auto');
+      setTimeouts(DEFAULT_TIMEOUTS);
+      setRetry(DEFAULT_RETRY_POLICY);
       setHeaders([]);
     }
     // Reset states
@@ -63,11 +76,15 @@ export const EndpointModal: React.FC<EndpointModalProps> = ({
         apiKey: apiKey || undefined,
         headers: headers.filter(([k]) => k.trim()),
         model,
+        apiStyle,
+        provider,
+        timeouts,
+        retry,
       };
       const models = await invoke<string[]>('fetch_models', { endpoint: tempEndpoint });
       setAvailableModels(models);
     } catch (error) {
-      alert(`Failed to fetch models: ${error}`);
+      alert(`Failed to fetch models: ${toAppError(error).message}`);
     } finally {
       setIsLoadingModels(false);
     }
@@ -89,11 +106,15 @@ export const EndpointModal: React.FC<EndpointModalProps> = ({
         apiKey: apiKey || undefined,
         headers: headers.filter(([k]) => k.trim()),
         model,
+        apiStyle,
+        provider,
+        timeouts,
+        retry,
       };
       const result = await invoke<string>('test_connection', { endpoint: tempEndpoint });
       setConnectionStatus({ type: 'success', message: result });
     } catch (error) {
-      setConnectionStatus({ type: 'error', message: String(error) });
+      setConnectionStatus({ type: 'error', message: toAppError(error).message });
     } finally {
       setIsTestingConnection(false);
     }
@@ -113,6 +134,22 @@ export const EndpointModal: React.FC<EndpointModalProps> = ({
     setHeaders(newHeaders);
   };
 
+  const updateTimeout = (field: keyof TimeoutConfig, value: string) => {
+    setTimeouts({ ...timeouts, [field]: value === '' ? null : Number(value) });
+  };
+
+  const timeoutFields: [keyof TimeoutConfig, string][] = [
+    ['connectMs', 'Connect'],
+    ['readMs', 'Read'],
+    ['totalMs', 'Total'],
+    ['idleStreamMs', 'Stream idle'],
+  ];
+
+  const updateRetry = (field: 'maxAttempts' | 'initialBackoffMs' | 'maxBackoffMs', value: string) => {
+    const fallback = DEFAULT_RETRY_POLICY[field];
+    setRetry({ ...retry, [field]: value === '' ? fallback : Math.max(field === 'maxAttempts' ? 1 : 0, Number(value)) });
+  };
+
   const handleSubmit = (e: React.FormEvent) => {
     e.preventDefault();
 
@@ -128,6 +165,10 @@ export const EndpointModal: React.FC<EndpointModalProps> = ({
       apiKey: apiKey || undefined,
       headers: headers.filter(([k]) => k.trim()),
       model,
+      apiStyle,
+      provider,
+      timeouts,
+      retry,
     };
 
     onSave(newEndpoint);
@@ -208,6 +249,43 @@ export const EndpointModal: React.FC<EndpointModalProps> = ({
             )}
           </div>
 
+          <div>
+            <Label htmlFor="apiStyle">API Style</Label>
+            <select
+              id="apiStyle"
+              value={apiStyle}
+              onChange={(e) => setApiStyle(e.target.value as ApiStyle)}
+              className="w-full px-3 py-2 bg-background border border-input rounded-md focus:outline-none focus:ring-2 focus:ring-ring"
+            >
+              <option value="chat_completions">OpenAI-compatible (/chat/completions)</option>
+              <option value="anthropic_messages">Anthropic Messages (/messages)</option>
+              <option value="gemini_generate_content">Google Gemini (:generateContent)</option>
+              <option value="responses">OpenAI Responses (/responses)</option>
+              <option value="completions">Text completions (/completions)</option>
+            </select>
+          </div>
+
+          <div>
+            <Label htmlFor="provider">Provider</Label>
+            <select
+              id="provider"
+              value={provider}
+              onChange={(e) => setProvider(e.target.value as ProviderSetting)}
+              className="w-full px-3 py-2 bg-background border border-input rounded-md focus:outline-none focus:ring-2 focus:ring-ring"
+            >
+              <option value="auto">Auto-detect (model name and /models metadata)</option>
+              <option value="generic">Generic (no reasoning parameters)</option>
+              <option value="openai">OpenAI</option>
+              <option value="deepseek">DeepSeek</option>
+              <option value="qwen">Qwen</option>
+              <option value="claude">Claude</option>
+              <option value="gemini">Gemini</option>
+            </select>
+            <p className="text-xs text-muted-foreground mt-1">
+              Set this when the model name does not reveal the provider, e.g. a fine-tune or an alias
+            </p>
+          </div>
+
           <div>
             <Label htmlFor="apiKey">API Key (Optional)</Label>
             <Input
@@ -313,6 +391,75 @@ export const EndpointModal: React.FC<EndpointModalProps> = ({
             )}
           </div>
 
+          <div>
+            <Label>Timeouts (ms)</Label>
+            <div className="grid grid-cols-4 gap-2">
+              {timeoutFields.map(([field, label]) => (
+                <div key={field}>
+                  <Label htmlFor={field} className="text-xs text-muted-foreground">
+                    {label}
+                  </Label>
+                  <Input
+                    id={field}
+                    type="number"
+                    min={1}
+                    placeholder="None"
+                    value={timeouts[field] ?? ''}
+                    onChange={(e) => updateTimeout(field, e.target.value)}
+                  />
+                </div>
+              ))}
+            </div>
+            <p className="text-xs text-muted-foreground mt-1">
+              Leave a field empty for no limit. Read covers waiting for headers (and the whole body without streaming); stream idle is the longest gap between chunks
+            </p>
+          </div>
+
+          <div>
+            <Label>Retries</Label>
+            <div className="grid grid-cols-3 gap-2">
+              <div>
+                <Label htmlFor="maxAttempts" className="text-xs text-muted-foreground">
+                  Attempts
+                </Label>
+                <Input
+                  id="maxAttempts"
+                  type="number"
+                  min={1}
+                  value={retry.maxAttempts}
+                  onChange={(e) => updateRetry('maxAttempts', e.target.value)}
+                />
+              </div>
+              <div>
+                <Label htmlFor="initialBackoffMs" className="text-xs text-muted-foreground">
+                  Initial backoff (ms)
+                </Label>
+                <Input
+                  id="initialBackoffMs"
+                  type="number"
+                  min={0}
+                  value={retry.initialBackoffMs}
+                  onChange={(e) => updateRetry('initialBackoffMs', e.target.value)}
+                />
+              </div>
+              <div>
+                <Label htmlFor="maxBackoffMs" className="text-xs text-muted-foreground">
+                  Max backoff (ms)
+                </Label>
+                <Input
+                  id="maxBackoffMs"
+                  type="number"
+                  min={0}
+                  value={retry.maxBackoffMs}
+                  onChange={(e) => updateRetry('maxBackoffMs', e.target.value)}
+                />
+              </div>
+            </div>
+            <p className="text-xs text-muted-foreground mt-1">
+              Retries HTTP {retry.retryStatuses.join(', ')}, connection failures and timeouts, honouring Retry-After. A wait longer than the max backoff fails the request; streams are never restarted once the body has started
+            </p>
+          </div>
+
           <div className="flex justify-end gap-2 pt-4 border-t border-border">
             <Button type="button" variant="outline" onClick={onClose}>
               Cancel
@@ -1,17 +1,19 @@
 import React, { useState } from 'react';
-import { Send, ChevronDown, ChevronUp, MessageSquare } from 'lucide-react';
+import { Send, Square, ChevronDown, ChevronUp, MessageSquare } from 'lucide-react';
 import { Button } from './UI/Button';
 import { Textarea } from './UI/Textarea';
 import { Label } from './UI/Label';
 
 interface PromptInputProps {
   onSubmit: (prompt: string, systemMessage: string) => void;
+  onCancel?: () => void;
   isLoading?: boolean;
   disabled?: boolean;
 }
 
 export const PromptInput: React.FC<PromptInputProps> = ({
   onSubmit,
+  onCancel,
   isLoading = false,
   disabled = false,
 }) => {
@@ -103,14 +105,22 @@ export const PromptInput: React.FC<PromptInputProps> = ({
               <div className="text-xs text-muted-foreground">
                 {prompt.length} characters · {prompt.split(/\s+/).filter(Boolean).length} words
               </div>
-              <Button
-                type="submit"
-                disabled={!prompt.trim() || disabled || isLoading}
-                size="lg"
-              >
-                <Send className="w-4 h-4 mr-2" />
-                {isLoading ? 'Sending...' : 'Send'}
-              </Button>
+              <div className="flex items-center gap-2">
+                {isLoading && onCancel && (
+                  <Button type="button" variant="outline" size="lg" onClick={onCancel}>
+                    <Square className="w-4 h-4 mr-2" />
+                    Stop
+                  </Button>
+                )}
+                <Button
+                  type="submit"
+                  disabled={!prompt.trim() || disabled || isLoading}
+                  size="lg"
+                >
+                  <Send className="w-4 h-4 mr-2" />
+                  {isLoading ? 'Sending...' : 'Send'}
+                </Button>
+              </div>
             </div>
           </form>
         </div>
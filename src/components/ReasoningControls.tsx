@@ -176,6 +176,49 @@ export const ReasoningControls: React.FC<ReasoningControlsProps> = ({
     </>
   );
 
+  const renderGeminiControls = () => (
+    <>
+      <div>
+        <label className="flex items-center gap-2 text-sm cursor-pointer">
+          <input
+            type="checkbox"
+            checked={config.enableThinking}
+            onChange={(e) => updateConfig({ enableThinking: e.target.checked })}
+            disabled={disabled}
+            className="w-4 h-4 rounded border-input"
+          />
+          <span>Enable Thinking Mode</span>
+        </label>
+        <p className="text-xs text-muted-foreground mt-1">
+          When off, the thinking budget is set to 0 (not supported by every model)
+        </p>
+      </div>
+
+      {config.enableThinking && (
+        <div className="mt-4">
+          <Label htmlFor="thinkingBudgetTokens" className="text-xs">
+            Thinking Budget (tokens)
+          </Label>
+          <Input
+            id="thinkingBudgetTokens"
+            type="number"
+            min="1"
+            max="32768"
+            value={config.thinkingBudgetTokens || 8000}
+            onChange={(e) =>
+              updateConfig({ thinkingBudgetTokens: parseInt(e.target.value) || 8000 })
+            }
+            disabled={disabled}
+            className="mt-2"
+          />
+          <p className="text-xs text-muted-foreground mt-1">
+            Sent as <code>thinkingConfig.thinkingBudget</code>
+          </p>
+        </div>
+      )}
+    </>
+  );
+
   return (
     <div className="bg-card border-t border-border">
       <button
@@ -203,7 +246,8 @@ export const ReasoningControls: React.FC<ReasoningControlsProps> = ({
               <>
                 <strong>OpenAI Reasoning Models:</strong> Uses{' '}
                 <code>reasoning_effort</code> parameter to control thinking depth.
-                Reasoning content is not exposed in API responses (token count only).
+                Chat completions only report the reasoning token count; use the
+                Responses API style to get reasoning summaries.
               </>
             )}
             {provider === 'deepseek' && (
@@ -226,12 +270,20 @@ export const ReasoningControls: React.FC<ReasoningControlsProps> = ({
                 tasks. Thinking blocks are shown separately from the main response.
               </>
             )}
+            {provider === 'gemini' && (
+              <>
+                <strong>Gemini Thinking Models:</strong> Thought summaries are requested
+                with <code>includeThoughts</code> and shown as thinking blocks; thought
+                tokens are reported as reasoning tokens.
+              </>
+            )}
           </div>
 
           {provider === 'openai' && renderOpenAIControls()}
           {provider === 'deepseek' && renderDeepSeekControls()}
           {provider === 'qwen' && renderQwenControls()}
           {provider === 'claude' && renderClaudeControls()}
+          {provider === 'gemini' && renderGeminiControls()}
         </div>
       )}
     </div>
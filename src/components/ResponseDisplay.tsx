@@ -108,6 +108,16 @@ export const ResponseDisplay: React.FC<ResponseDisplayProps> = ({
   };
 
   const hasReasoning = reasoningContent || (thinkingBlocks && thinkingBlocks.length > 0);
+  const connectionNote =
+    metrics?.connectionReused == null
+      ? ''
+      : metrics.connectionReused
+        ? ', reused connection'
+        : ', new connection';
+  const retryNote =
+    metrics?.retries && metrics.retries.attempts > 1
+      ? `, attempt ${metrics.retries.attempts} after ${(metrics.retries.failedAttemptsMs + metrics.retries.waitMs).toFixed(0)}ms of retries`
+      : '';
 
   return (
     <div className="flex-1 flex flex-col overflow-hidden">
@@ -144,7 +154,12 @@ export const ResponseDisplay: React.FC<ResponseDisplayProps> = ({
             <MetricCard
               label="TTFT"
               value={`${metrics.ttftMs.toFixed(0)}ms`}
-              tooltip="Time to First Token"
+              tooltip={
+                metrics.timeToFirstReasoningMs !== undefined &&
+                metrics.timeToFirstAnswerMs !== undefined
+                  ? `Time to First Token (first reasoning token ${metrics.timeToFirstReasoningMs.toFixed(0)}ms, first answer token ${metrics.timeToFirstAnswerMs.toFixed(0)}ms)${connectionNote}${retryNote}`
+                  : `Time to First Token${connectionNote}${retryNote}`
+              }
             />
             <MetricCard
               label="TPOT"
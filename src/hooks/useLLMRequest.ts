@@ -1,11 +1,14 @@
 import { useState, useCallback, useRef, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/tauri';
 import { listen } from '@tauri-apps/api/event';
-import { Endpoint, LLMRequest, LLMResponse, PerformanceMetrics, ThinkingBlock } from '../types';
+import { AppError, Endpoint, LLMRequest, LLMResponse, PerformanceMetrics, StreamingResult, ThinkingBlock } from '../types';
+import { toAppError } from '../utils/errors';
 
 interface UseLLMRequestReturn {
   isLoading: boolean;
-  error: string | null;
+  error: AppError | null;
+  /** Problems that did not fail the last request, e.g. it was not saved to history */
+  warnings: string[];
   response: string;
   metrics: PerformanceMetrics | null;
   reasoningContent?: string;
@@ -16,22 +19,23 @@ interface UseLLMRequestReturn {
     request: LLMRequest,
     onToken?: (token: string) => void
   ) => Promise<void>;
+  cancelRequest: () => Promise<void>;
   clearResponse: () => void;
   dismissError: () => void;
+  dismissWarnings: () => void;
 }
 
 export function useLLMRequest(): UseLLMRequestReturn {
   const [isLoading, setIsLoading] = useState(false);
-  const [error, setError] = useState<string | null>(null);
+  const [error, setError] = useState<AppError | null>(null);
+  const [warnings, setWarnings] = useState<string[]>([]);
   const [response, setResponse] = useState('');
   const [metrics, setMetrics] = useState<PerformanceMetrics | null>(null);
   const [reasoningContent, setReasoningContent] = useState<string>();
   const [thinkingBlocks, setThinkingBlocks] = useState<ThinkingBlock[]>();
   const [reasoningProvider, setReasoningProvider] = useState<string | null>();
 
-  const startTimeRef = useRef<number>(0);
-  const firstTokenTimeRef = useRef<number | null>(null);
-  const tokenTimestampsRef = useRef<number[]>([]);
+  const requestIdRef = useRef<string | null>(null);
   const errorTimeoutRef = useRef<ReturnType<typeof setTimeout> | null>(null);
 
   // Auto-dismiss error after 5 seconds
@@ -57,108 +61,85 @@ export function useLLMRequest(): UseLLMRequestReturn {
     ) => {
       setIsLoading(true);
       setError(null);
+      setWarnings([]);
       setResponse('');
       setMetrics(null);
       setReasoningContent(undefined);
       setThinkingBlocks(undefined);
       setReasoningProvider(undefined);
 
-      // Reset metrics tracking
-      startTimeRef.current = performance.now();
-      firstTokenTimeRef.current = null;
-      tokenTimestampsRef.current = [];
+      const requestId = crypto.randomUUID();
+      requestIdRef.current = requestId;
 
       try {
         if (request.stream) {
           // Set up token listener
           const unlisten = await listen<string>('token', (event) => {
             const token = event.payload;
-            const now = performance.now();
-
-            if (firstTokenTimeRef.current === null && token.trim().length > 0) {
-              firstTokenTimeRef.current = now;
-            }
-
-            tokenTimestampsRef.current.push(now);
             setResponse((prev) => prev + token);
             onToken?.(token);
           });
 
+          const unlistenReasoning = await listen<string>('reasoning_token', (event) => {
+            setReasoningContent((prev) => (prev ?? '') + event.payload);
+          });
+
           try {
-            await invoke('send_streaming_request', {
+            // Metrics are measured by the backend as SSE chunks arrive,
+            // so they are free of IPC jitter
+            const result = await invoke<StreamingResult>('send_streaming_request', {
               endpoint,
               request,
+              requestId,
             });
+            setResponse(result.content);
+            setReasoningContent(result.reasoningContent);
+            if (result.thinkingBlocks.length > 0) {
+              // Thinking blocks replace the flat live reasoning view
+              setReasoningContent(undefined);
+              setThinkingBlocks(result.thinkingBlocks);
+            }
+            setMetrics(result.metrics);
+            setWarnings(result.warnings ?? []);
           } finally {
             unlisten();
+            unlistenReasoning();
           }
-
-          // Calculate metrics
-          const endTime = performance.now();
-          const ttft = firstTokenTimeRef.current
-            ? firstTokenTimeRef.current - startTimeRef.current
-            : 0;
-
-          const totalLatency = endTime - startTimeRef.current;
-
-          // Calculate TPOT (Time Per Output Token)
-          const tpotSamples: number[] = [];
-          for (let i = 1; i < tokenTimestampsRef.current.length; i++) {
-            tpotSamples.push(
-              tokenTimestampsRef.current[i] - tokenTimestampsRef.current[i - 1]
-            );
-          }
-
-          const avgTpot =
-            tpotSamples.length > 0
-              ? tpotSamples.reduce((a, b) => a + b, 0) / tpotSamples.length
-              : undefined;
-
-          const tokensPerSecond =
-            totalLatency > 0
-              ? (tokenTimestampsRef.current.length / totalLatency) * 1000
-              : undefined;
-
-          setMetrics({
-            ttftMs: ttft,
-            avgTpotMs: avgTpot,
-            totalLatencyMs: totalLatency,
-            totalTokens: tokenTimestampsRef.current.length,
-            tokensPerSecond,
-          });
         } else {
           const result = await invoke<LLMResponse>('send_request', {
             endpoint,
             request,
+            requestId,
           });
 
           setResponse(result.content);
           setReasoningContent(result.reasoningContent);
           setThinkingBlocks(result.thinkingBlocks);
           setReasoningProvider(result.reasoningProvider || null);
-
-          const endTime = performance.now();
-          const totalLatency = endTime - startTimeRef.current;
-
-          setMetrics({
-            ttftMs: totalLatency,
-            totalLatencyMs: totalLatency,
-            totalTokens: result.usage?.completionTokens || 0,
-          });
+          setMetrics(result.metrics);
+          setWarnings(result.warnings ?? []);
         }
       } catch (err) {
-        setError(err as string);
+        setError(toAppError(err));
         console.error('Request failed:', err);
       } finally {
+        requestIdRef.current = null;
         setIsLoading(false);
       }
     },
     []
   );
 
+  const cancelRequest = useCallback(async () => {
+    if (requestIdRef.current) {
+      await invoke<boolean>('cancel_request', { requestId: requestIdRef.current });
+    }
+  }, []);
+
   const clearResponse = useCallback(() => {
     setResponse('');
     setError(null);
+    setWarnings([]);
     setMetrics(null);
     setReasoningContent(undefined);
     setThinkingBlocks(undefined);
@@ -169,16 +150,23 @@ export function useLLMRequest(): UseLLMRequestReturn {
     setError(null);
   }, []);
 
+  const dismissWarnings = useCallback(() => {
+    setWarnings([]);
+  }, []);
+
   return {
     isLoading,
     error,
+    warnings,
     response,
     metrics,
     reasoningContent,
     thinkingBlocks,
     reasoningProvider,
     sendRequest,
+    cancelRequest,
     clearResponse,
     dismissError,
+    dismissWarnings,
   };
 }
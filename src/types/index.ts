@@ -1,3 +1,6 @@
+/** Wire protocol used to talk to an endpoint */
+export type ApiStyle = 'chat_completions' | 'anthropic_messages' | 'gemini_generate_content' | 'responses' | 'completions';
+
 export interface Endpoint {
   id: string;
   name: string;
@@ -5,9 +8,82 @@ export interface Endpoint {
   apiKey?: string;
   headers: [string, string][];
   model: string;
+  /** Wire protocol spoken by the endpoint (defaults to chat_completions) */
+  apiStyle?: ApiStyle;
+  /** Provider family of the endpoint's models (defaults to auto) */
+  provider?: ProviderSetting;
+  /** Request timeouts (defaults to DEFAULT_TIMEOUTS) */
+  timeouts?: TimeoutConfig;
+  /** Retries of failed requests (defaults to DEFAULT_RETRY_POLICY: sent once) */
+  retry?: RetryPolicy;
+}
+
+/** Per-endpoint timeouts in milliseconds; `null` disables one */
+export interface TimeoutConfig {
+  /** Establishing a new connection (TCP and TLS) */
+  connectMs: number | null;
+  /**
+   * From sending the request (connecting included) to its response headers. Without
+   * streaming, reading the body then gets a window of the same length of its own.
+   */
+  readMs: number | null;
+  /** The whole request, from sending it to the end of the body */
+  totalMs: number | null;
+  /** Longest gap between two chunks of a streamed body */
+  idleStreamMs: number | null;
+}
+
+export const DEFAULT_TIMEOUTS: TimeoutConfig = {
+  connectMs: 10_000,
+  readMs: 600_000,
+  totalMs: null,
+  idleStreamMs: 120_000,
+};
+
+/** Failures without a response that can be retried */
+export type RetryableError = 'connect' | 'timeout';
+
+/**
+ * When and how often a failed request is sent again. Only failures before any of the
+ * response body was read are retried, so a stream is never restarted midway.
+ */
+export interface RetryPolicy {
+  /** Attempts in total, including the first; 1 disables retries */
+  maxAttempts: number;
+  /** Backoff before the first retry, doubling for each retry after it */
+  initialBackoffMs: number;
+  /** Cap on the backoff; a longer wait asked for by the server fails the request */
+  maxBackoffMs: number;
+  /** Response statuses worth retrying */
+  retryStatuses: number[];
+  retryErrors: RetryableError[];
 }
 
-export type ReasoningProvider = 'openai' | 'deepseek' | 'qwen' | 'claude';
+export const DEFAULT_RETRY_POLICY: RetryPolicy = {
+  maxAttempts: 1,
+  initialBackoffMs: 500,
+  maxBackoffMs: 30_000,
+  retryStatuses: [408, 429, 500, 502, 503, 504],
+  retryErrors: ['connect', 'timeout'],
+};
+
+export type ReasoningProvider = 'openai' | 'deepseek' | 'qwen' | 'claude' | 'gemini';
+
+/** Provider configured on an endpoint; `generic` sends no reasoning parameters */
+export type ProviderSetting = 'auto' | 'generic' | ReasoningProvider;
+
+/** Where a request's provider came from */
+export type ProviderSource = 'endpoint' | 'api_style' | 'model_name' | 'model_metadata' | 'default';
+
+/** Provider and adapter chosen for a request, and why */
+export interface ProviderResolution {
+  /** Adapter that built the request and parsed the response */
+  adapter: string;
+  provider?: ReasoningProvider;
+  source: ProviderSource;
+  /** Human-readable explanation, e.g. which pattern or metadata field matched */
+  reason: string;
+}
 
 export interface ReasoningConfig {
   /** Enable reasoning mode (for hybrid models like Qwen) */
@@ -16,13 +92,69 @@ export interface ReasoningConfig {
   reasoningEffort?: string;
   /** Maximum completion tokens for OpenAI reasoning models */
   maxCompletionTokens?: number;
-  /** Thinking budget tokens for Claude/Qwen */
+  /** Thinking budget tokens for Claude/Qwen/Gemini */
   thinkingBudgetTokens?: number;
 }
 
+/** One part of multimodal message content (OpenAI format) */
+export type ContentPart =
+  | { type: 'text'; text: string }
+  | {
+      type: 'image_url';
+      image_url: {
+        /** http(s) or data URL; local file paths are encoded by the backend */
+        url: string;
+        detail?: 'low' | 'high' | 'auto';
+      };
+    }
+  | {
+      type: 'input_audio';
+      input_audio: {
+        /** Base64 audio, or a `file://` URL for the backend to encode */
+        data: string;
+        format: 'wav' | 'mp3';
+      };
+    };
+
+export type MessageContent = string | ContentPart[];
+
 export interface Message {
-  role: 'system' | 'user' | 'assistant';
-  content: string;
+  role: 'system' | 'user' | 'assistant' | 'tool';
+  content: MessageContent;
+  /** Calls made by an assistant message */
+  toolCalls?: ToolCall[];
+  /** For `tool` messages: the call this is the result of */
+  toolCallId?: string;
+}
+
+export interface FunctionDefinition {
+  name: string;
+  description?: string;
+  /** JSON Schema for the arguments */
+  parameters?: Record<string, unknown>;
+}
+
+export interface Tool {
+  type: 'function';
+  function: FunctionDefinition;
+}
+
+export interface ToolCall {
+  id: string;
+  type: 'function';
+  function: {
+    name: string;
+    /** JSON-encoded arguments, exactly as generated */
+    arguments: string;
+  };
+}
+
+export interface ToolCallTiming {
+  /** Position in the result's `toolCalls` */
+  index: number;
+  startedMs: number;
+  /** When the call's arguments finished streaming */
+  completedMs?: number;
 }
 
 export interface LLMRequest {
@@ -31,21 +163,73 @@ export interface LLMRequest {
   temperature: number;
   maxTokens: number;
   stream: boolean;
+  tools?: Tool[];
+  /** `"auto"`, `"none"`, `"required"` or `{ type: "function", function: { name } }` */
+  toolChoice?: string | Record<string, unknown>;
+  /** Structured output mode; the response content is validated against it */
+  responseFormat?: ResponseFormat;
+  /** Raw prompt for the completions API style, sent without a chat template */
+  prompt?: string;
+  /** Return the prompt in front of the completion (completions API style) */
+  echo?: boolean;
+  /** Text that follows the completion, for fill-in-the-middle (completions API style) */
+  suffix?: string;
+  /** Return the log probability of each output token */
+  logprobs?: boolean;
+  /** Also return the N most likely alternatives at each position */
+  topLogprobs?: number;
   /** Reasoning configuration for supported models */
   reasoningConfig?: ReasoningConfig;
 }
 
+export type ResponseFormat =
+  | { type: 'text' }
+  | { type: 'json_object' }
+  | {
+      type: 'json_schema';
+      json_schema: {
+        name: string;
+        description?: string;
+        schema: Record<string, unknown>;
+        strict?: boolean;
+      };
+    };
+
+/** One place where the output broke the schema */
+export interface SchemaViolation {
+  /** JSON Pointer to the offending value (empty for the document root) */
+  path: string;
+  message: string;
+}
+
+/** Result of checking response content against the requested `responseFormat` */
+export interface OutputValidation {
+  /** Whether the content parsed as JSON */
+  parsed: boolean;
+  /** Whether it parsed and matched the schema (or, for `json_object`, was an object) */
+  valid: boolean;
+  /** JSON parse error, with line and column */
+  parseError?: string;
+  errors: SchemaViolation[];
+  /** Time spent parsing and validating, not included in the request latency */
+  validationMs: number;
+}
+
 export interface UsageMetrics {
   promptTokens: number;
   completionTokens: number;
   totalTokens: number;
   /** Reasoning tokens (OpenAI only - counts but not exposes content) */
   reasoningTokens?: number;
+  /** Prompt tokens written to the prompt cache (Anthropic) */
+  cacheCreationTokens?: number;
+  /** Prompt tokens served from the prompt cache */
+  cacheReadTokens?: number;
 }
 
 export interface ThinkingBlock {
   content: string;
-  /** For Claude 4.x where thinking is summarized */
+  /** For Claude 4.x where thinking is summarized, and OpenAI Responses reasoning summaries */
   summary?: string;
 }
 
@@ -59,6 +243,39 @@ export interface LLMResponse {
   thinkingBlocks?: ThinkingBlock[];
   /** Detected reasoning provider */
   reasoningProvider?: ReasoningProvider;
+  /** Whether the request was cancelled before the response arrived */
+  cancelled: boolean;
+  /** Backend-measured timing (TTFT equals total latency without streaming) */
+  metrics: PerformanceMetrics;
+  toolCalls: ToolCall[];
+  /** Set when the request asked for JSON output */
+  validation?: OutputValidation;
+  /** How the provider was chosen for this request */
+  providerResolution?: ProviderResolution;
+  /** Per-token log probabilities, when the request asked for them */
+  logprobs?: TokenLogprob[];
+  /** Summary of `logprobs` */
+  logprobStats?: LogprobStats;
+  /** Problems that did not fail the request, e.g. it could not be saved to history */
+  warnings?: string[];
+}
+
+/** Log probability of one output token */
+export interface TokenLogprob {
+  token: string;
+  logprob: number;
+  /** Most likely tokens at this position, most likely first */
+  topLogprobs?: { token: string; logprob: number }[];
+}
+
+/** Confidence summary of a response, for comparing deployments of the same model */
+export interface LogprobStats {
+  tokenCount: number;
+  meanLogprob: number;
+  /** `exp(-meanLogprob)` */
+  perplexity: number;
+  /** Tokens the model was least sure of, least likely first */
+  lowestTokens: { index: number; token: string; logprob: number }[];
 }
 
 export interface PerformanceMetrics {
@@ -67,20 +284,349 @@ export interface PerformanceMetrics {
   totalLatencyMs: number;
   totalTokens: number;
   tokensPerSecond?: number;
+  /** Time to first reasoning/thinking token (streaming reasoning models only) */
+  timeToFirstReasoningMs?: number;
+  /** Time to first answer token, after any reasoning (streaming only) */
+  timeToFirstAnswerMs?: number;
+  /** Number of SSE chunks that carried reasoning; not a token count (see usage.reasoningTokens) */
+  reasoningChunks: number;
+  /** Number of SSE chunks that carried output (0 for non-streaming requests) */
+  streamChunks: number;
+  /** When each streamed tool call started and finished */
+  toolCalls: ToolCallTiming[];
+  /** Size of the request body sent, including any base64-encoded media */
+  requestBytes: number;
+  /** Whether the request reused a pooled connection, so no TCP/TLS setup is in TTFT */
+  connectionReused?: boolean | null;
+  /** Where the time went on the network, from DNS lookup to the end of the body */
+  network?: NetworkTimings | null;
+  /** Attempts made; the timings above cover the last one */
+  retries?: RetryStats | null;
+}
+
+/** Attempts made under the endpoint's retry policy and the time lost to failed ones */
+export interface RetryStats {
+  attempts: number;
+  /** Time spent in the attempts that failed */
+  failedAttemptsMs: number;
+  /** Time spent waiting between attempts */
+  waitMs: number;
+  /** Why each failed attempt failed, in order */
+  errors: string[];
+}
+
+/**
+ * When each network phase of a request finished, in ms since it was sent (cumulative,
+ * like curl's -w timings). A phase that did not happen is null.
+ */
+export interface NetworkTimings {
+  /** DNS lookup done; null on a reused connection or an IP address URL */
+  dnsMs: number | null;
+  /** New connection ready: TCP connect plus, on https, the TLS handshake */
+  connectMs: number | null;
+  /** Request body handed to the connection */
+  uploadMs: number | null;
+  /** Response status and headers received */
+  headersMs: number | null;
+  /** First byte of the response body */
+  firstByteMs: number | null;
+  /** First output token (the end of the body without streaming, as for TTFT) */
+  firstTokenMs: number | null;
+  /** Body fully received */
+  transferEndMs: number | null;
+}
+
+/** What went wrong, so the UI can tell e.g. a bad API key from a rate limit */
+export type ErrorKind =
+  | 'auth'
+  | 'not_found'
+  | 'rate_limit'
+  | 'context_length'
+  | 'invalid_request'
+  | 'server'
+  | 'timeout'
+  | 'connection'
+  | 'malformed_response'
+  | 'other';
+
+/** Error returned by every command in place of a bare message */
+export interface AppError {
+  kind: ErrorKind;
+  /** Human-readable summary, including the status and provider message when known */
+  message: string;
+  /** HTTP status of the failed response; null when no response was received */
+  status: number | null;
+  /** Provider's error code or type, e.g. `context_length_exceeded` or `overloaded_error` */
+  providerCode: string | null;
+  /** Message parsed out of the provider's error body */
+  providerMessage: string | null;
+  /** Headers of the failed response, for request IDs and rate limit details */
+  headers: [string, string][];
+  /** Whether sending the same request again may succeed */
+  retryable: boolean;
+  /** Attempts made under the endpoint's retry policy, including the one that failed */
+  retries?: RetryStats | null;
+  /** Problems besides the failure itself, e.g. the request could not be saved to history */
+  warnings?: string[];
+}
+
+/** Result of a streaming request, timed at the network layer */
+export interface StreamingResult {
+  requestId: string;
+  /** Content received so far (partial if cancelled) */
+  content: string;
+  /** Streamed reasoning content from DeepSeek/Qwen */
+  reasoningContent?: string;
+  /** Thinking blocks streamed by the Anthropic Messages API */
+  thinkingBlocks: ThinkingBlock[];
+  /** Server-reported usage from the final stream chunk(s) */
+  usage?: UsageMetrics;
+  metrics: PerformanceMetrics;
+  /** Whether the stream was cancelled before completion */
+  cancelled: boolean;
+  /** Finish reason from the final chunk(s), if the server sent one */
+  finishReason?: string;
+  /** Tool calls reassembled from streamed fragments */
+  toolCalls: ToolCall[];
+  /** Set when the request asked for JSON output */
+  validation?: OutputValidation;
+  /** How the provider was chosen for this request */
+  providerResolution?: ProviderResolution;
+  /** Per-token log probabilities, when the request asked for them */
+  logprobs?: TokenLogprob[];
+  /** Summary of `logprobs` */
+  logprobStats?: LogprobStats;
+  /** Problems that did not fail the request, e.g. it could not be saved to history */
+  warnings?: string[];
 }
 
 export interface RequestHistoryItem {
   id: string;
   timestamp: number;
+  endpointId: string;
   endpointName: string;
+  endpointUrl: string;
   model: string;
   prompt: string;
   response: string;
   metrics: PerformanceMetrics;
   stream: boolean;
+  /** Full request as sent to the endpoint */
+  request?: LLMRequest;
+  /** Parsed response (non-streaming requests only) */
+  llmResponse?: LLMResponse;
+  /** Streamed result with its usage, reasoning and tool calls (streaming requests only) */
+  streamingResult?: StreamingResult;
+  /** Whether the request was cancelled before completion */
+  cancelled: boolean;
+  /** Error text if the request failed */
+  error?: string;
 }
 
-export interface TokenEvent {
+export interface BenchmarkConfig {
+  totalRequests: number;
+  /** Maximum number of requests in flight */
+  concurrency: number;
+  /** Fixed arrival rate, kept however many requests are still in flight; when unset requests start as soon as a slot frees up */
+  targetQps?: number;
+  /** TTFT a successful request must meet to count towards goodput */
+  sloTtftMs?: number;
+  /** TPOT a successful request must meet to count towards goodput */
+  sloTpotMs?: number;
+}
+
+export interface BenchmarkSample {
+  index: number;
+  /** When the request was sent, relative to the benchmark start */
+  startOffsetMs: number;
+  /** How long the request waited for a free slot after its scheduled release; only with a target QPS */
+  queueMs?: number;
+  success: boolean;
+  error?: string;
+  usage?: UsageMetrics;
+  metrics: PerformanceMetrics;
+}
+
+export interface LatencyStats {
+  min: number;
+  mean: number;
+  p50: number;
+  p90: number;
+  p99: number;
+  max: number;
+}
+
+export interface BenchmarkReport {
+  benchmarkId: string;
+  config: BenchmarkConfig;
+  completed: number;
+  succeeded: number;
+  failed: number;
+  cancelled: boolean;
+  durationMs: number;
+  errorRate: number;
+  /** Requests that needed more than one attempt; their latency covers the last one */
+  retried: number;
+  /** Time lost to failed attempts and waiting between them, summed across requests */
+  retryMs: number;
+  requestsPerSecond: number;
+  /** Successful requests per second that met the configured SLOs */
+  goodput: number;
+  outputTokensPerSecond: number;
+  ttft?: LatencyStats;
+  tpot?: LatencyStats;
+  latency?: LatencyStats;
+  /** Queueing delay of every request, with a target QPS */
+  queue?: LatencyStats;
+  errors: { message: string; count: number }[];
+  samples: BenchmarkSample[];
+}
+
+/** Payload of the `benchmark_progress` event */
+export interface BenchmarkProgress {
+  benchmarkId: string;
+  completed: number;
+  total: number;
+  succeeded: number;
+  failed: number;
+  elapsedMs: number;
+}
+
+export interface BatchConfig {
+  /** Maximum number of lines in flight; results are still written in input order */
+  concurrency: number;
+  /** Force streaming on or off for every line instead of using each line's `stream` */
+  stream?: boolean;
+}
+
+/** One line of a batch results file */
+export interface BatchResult {
+  /** 1-based line number in the input file */
+  line: number;
+  /** `custom_id` from OpenAI Batch API style input lines */
+  customId?: string;
+  success: boolean;
+  error?: string;
+  content: string;
+  reasoningContent?: string;
+  /** Finish reason; streamed lines only have one if the server sent it */
+  finishReason?: string;
+  toolCalls: ToolCall[];
+  usage?: UsageMetrics;
+  metrics: PerformanceMetrics;
+  /** Set when the line asked for JSON output */
+  validation?: OutputValidation;
+}
+
+/** Payload of the `batch_progress` event */
+export interface BatchProgress {
+  batchId: string;
+  completed: number;
+  total: number;
+  result: BatchResult;
+}
+
+export interface BatchSummary {
+  batchId: string;
+  inputPath: string;
+  outputPath: string;
+  total: number;
+  completed: number;
+  succeeded: number;
+  failed: number;
+  /** Successful lines whose output failed JSON/schema validation */
+  invalidOutputs: number;
+  cancelled: boolean;
+  durationMs: number;
+  ttft?: LatencyStats;
+  latency?: LatencyStats;
+}
+
+export type DiffOp = 'equal' | 'insert' | 'delete';
+
+export interface DiffSegment {
+  op: DiffOp;
+  text: string;
+}
+
+/** Word-level diff of one endpoint's content against the baseline endpoint's */
+export interface ContentDiff {
+  /** Index of the baseline entry (the first successful endpoint) */
+  baselineIndex: number;
+  index: number;
+  /** Share of text in common with the baseline, from 0 to 1 */
+  similarity: number;
+  segments: DiffSegment[];
+}
+
+/** One endpoint's side of a comparison */
+export interface CompareEntry {
+  /** Position in the `endpoints` list, used to tag `compare_token` events */
+  index: number;
+  endpointId: string;
+  endpointName: string;
+  model: string;
+  success: boolean;
+  error?: AppError | null;
+  cancelled: boolean;
+  content: string;
+  reasoningContent?: string;
+  usage?: UsageMetrics;
+  metrics: PerformanceMetrics;
+  /** Problems that did not fail the request, e.g. it could not be saved to history */
+  warnings?: string[];
+}
+
+export interface CompareResult {
+  compareId: string;
+  entries: CompareEntry[];
+  diffs: ContentDiff[];
+}
+
+/** Payload of the `compare_token` event */
+export interface CompareToken {
+  compareId: string;
+  index: number;
+  endpointId: string;
   token: string;
+  /** Whether this is reasoning/thinking output rather than answer content */
+  reasoning: boolean;
+}
+
+/**
+ * One message in a conversation session. Assistant turns also keep their
+ * reasoning and the metrics of the request that produced them.
+ */
+export interface SessionTurn {
+  role: string;
+  content: MessageContent;
   timestamp: number;
+  requestId?: string;
+  model?: string;
+  reasoningContent?: string;
+  thinkingBlocks: ThinkingBlock[];
+  usage?: UsageMetrics;
+  metrics?: PerformanceMetrics;
+  toolCalls: ToolCall[];
+  toolCallId?: string;
+}
+
+export interface Session {
+  id: string;
+  title: string;
+  createdAt: number;
+  updatedAt: number;
+  /** Session this one was forked from */
+  parentId?: string;
+  turns: SessionTurn[];
+}
+
+/** Session metadata returned by `list_sessions` */
+export interface SessionSummary {
+  id: string;
+  title: string;
+  createdAt: number;
+  updatedAt: number;
+  parentId?: string;
+  turnCount: number;
 }
@@ -0,0 +1,40 @@
+import type { AppError, ErrorKind } from '../types';
+
+const ERROR_TITLES: Record<ErrorKind, string> = {
+  auth: 'Authentication failed',
+  not_found: 'Not found',
+  rate_limit: 'Rate limited',
+  context_length: 'Prompt too long for the context window',
+  invalid_request: 'Invalid request',
+  server: 'Server error',
+  timeout: 'Timed out',
+  connection: 'Connection failed',
+  malformed_response: 'Malformed response',
+  other: 'Error',
+};
+
+/**
+ * Normalize a rejected `invoke`: commands reject with an AppError, but Tauri itself
+ * rejects with a plain string (e.g. for an unknown command or bad arguments)
+ */
+export function toAppError(error: unknown): AppError {
+  if (typeof error === 'object' && error !== null && 'kind' in error && 'message' in error) {
+    return error as AppError;
+  }
+
+  return {
+    kind: 'other',
+    message: String(error),
+    status: null,
+    providerCode: null,
+    providerMessage: null,
+    headers: [],
+    retryable: false,
+  };
+}
+
+/** Short heading for an error, e.g. "Rate limited (429)" */
+export function errorTitle(error: AppError): string {
+  const title = ERROR_TITLES[error.kind] ?? ERROR_TITLES.other;
+  return error.status ? `${title} (${error.status})` : title;
+}
@@ -1,4 +1,4 @@
-import type { ReasoningProvider } from '../types';
+import type { Endpoint, ReasoningProvider } from '../types';
 
 /**
  * Detect reasoning provider from model name using flexible pattern matching
@@ -34,6 +34,12 @@ export function detectProvider(modelName: string): ReasoningProvider | null {
     return 'claude';
   }
 
+  // Gemini thinking models
+  // Matches: gemini-2.5-pro, gemini-2.5-flash, gemini-3-pro-preview, etc.
+  if (isGeminiModel(normalized)) {
+    return 'gemini';
+  }
+
   return null;
 }
 
@@ -53,8 +59,9 @@ function isDeepSeekModel(model: string): boolean {
 }
 
 function isQwenModel(model: string): boolean {
-  // Pattern: qwen, qwq with potential reasoning indicators
-  const qwenPattern = /^(qwen|qwq)([-_.]?(plus|max|turbo|coder|\d+\.?\d*))?$/;
+  // Pattern: qwen, qwq followed by any run of tier/version/size segments
+  // (qwen-plus, qwen3-max, qwq-32b, qwen2.5-72b-instruct)
+  const qwenPattern = /^(qwen|qwq)([-_.]?(plus|max|turbo|coder|\d+(\.\d+)?b?))*([-_.:]|$)/;
 
   return qwenPattern.test(model);
 }
@@ -66,6 +73,37 @@ function isClaudeModel(model: string): boolean {
   return claudePattern.test(model);
 }
 
+function isGeminiModel(model: string): boolean {
+  // Pattern: gemini 2.5 or later (earlier versions do not think)
+  const geminiPattern = /^gemini[-_]?(2\.5|[3-9])/;
+
+  return geminiPattern.test(model);
+}
+
+/**
+ * Reasoning provider for an endpoint: its explicit setting, else the API style,
+ * else detection from the model name. The backend additionally consults
+ * `/models` metadata and reports its choice in `providerResolution`.
+ * @param endpoint - The endpoint to inspect
+ * @returns The reasoning provider or null for plain chat models
+ */
+export function endpointProvider(endpoint: Endpoint): ReasoningProvider | null {
+  const setting = endpoint.provider ?? 'auto';
+  if (setting === 'generic') {
+    return null;
+  }
+  if (setting !== 'auto') {
+    return setting;
+  }
+  if (endpoint.apiStyle === 'anthropic_messages') {
+    return 'claude';
+  }
+  if (endpoint.apiStyle === 'gemini_generate_content') {
+    return 'gemini';
+  }
+  return detectProvider(endpoint.model);
+}
+
 /**
  * Check if a model is a reasoning model
  * @param modelName - The model name to check
@@ -86,6 +124,7 @@ export function getProviderName(provider: ReasoningProvider): string {
     deepseek: 'DeepSeek',
     qwen: 'Qwen',
     claude: 'Claude',
+    gemini: 'Gemini',
   };
   return names[provider];
 }